@@ -4,7 +4,8 @@ use bevy::{
     app::{App, Startup},
     asset::Assets,
     core_pipeline::core_3d::Camera3dBundle,
-    ecs::system::{Commands, ResMut},
+    ecs::system::{Commands, Res, ResMut},
+    input::{keyboard::KeyCode, ButtonInput},
     math::{
         primitives::{Rectangle, Sphere},
         Vec2, Vec3,
@@ -20,7 +21,8 @@ use bevy::{
     DefaultPlugins,
 };
 use bevy_xpbd_tutorial::{
-    BoxCollider, CircleCollider, ParticleBundle, Pos, StaticBoxBundle, XPBDPlugin,
+    BoxCollider, CircleCollider, DragPlugin, Interpolation, ParticleBundle, PhysicsTime, Pos,
+    StaticBoxBundle, XPBDPlugin,
 };
 
 fn main() {
@@ -30,9 +32,13 @@ fn main() {
         .add_plugins(DefaultPlugins)
         .add_plugins(LogDiagnosticsPlugin::default())
         .add_plugins(FrameTimeDiagnosticsPlugin)
-        .add_plugins(XPBDPlugin)
+        .add_plugins(XPBDPlugin::default())
+        .add_plugins(DragPlugin::default())
+        // keeps slow motion smooth when timesteps no longer land on every frame
+        .insert_resource(Interpolation::Interpolate)
         .add_systems(Startup, spawn_camera)
         .add_systems(Startup, spawn_balls)
+        .add_systems(Update, time_controls)
         .add_systems(Update, bevy::window::close_on_esc)
         .run();
 }
@@ -50,6 +56,27 @@ fn spawn_camera(mut commands: Commands) {
     });
 }
 
+/// space pauses, right arrow steps while paused and S toggles slow motion
+fn time_controls(keys: Res<ButtonInput<KeyCode>>, mut physics_time: ResMut<PhysicsTime>) {
+    if keys.just_pressed(KeyCode::Space) {
+        if physics_time.is_paused() {
+            physics_time.resume();
+        } else {
+            physics_time.pause();
+        }
+    }
+    if keys.just_pressed(KeyCode::ArrowRight) {
+        physics_time.step_once();
+    }
+    if keys.just_pressed(KeyCode::KeyS) {
+        physics_time.time_scale = if physics_time.time_scale < 1. {
+            1.
+        } else {
+            0.25
+        };
+    }
+}
+
 fn spawn_balls(
     mut commands: Commands,
     mut materials: ResMut<Assets<StandardMaterial>>,
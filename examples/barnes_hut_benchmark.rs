@@ -0,0 +1,63 @@
+//! Times the Barnes-Hut mutual gravity approximation against the exact pairwise sum and reports
+//! its error. Run with `cargo run --release --example barnes_hut_benchmark`.
+
+use std::time::{Duration, Instant};
+
+use bevy::math::Vec2;
+use bevy_xpbd_tutorial::{barnes_hut_accelerations, exact_accelerations, MutualGravitySettings};
+use rand::{rngs::StdRng, Rng, SeedableRng};
+
+const RUNS: u32 = 5;
+
+fn time<T>(mut f: impl FnMut() -> T) -> (T, Duration) {
+    let start = Instant::now();
+    let mut result = f();
+    for _ in 1..RUNS {
+        result = f();
+    }
+    (result, start.elapsed() / RUNS)
+}
+
+fn main() {
+    let mut rng = StdRng::seed_from_u64(0);
+    println!(
+        "{:>6} {:>6} {:>12} {:>12} {:>8} {:>10}",
+        "bodies", "theta", "exact", "barnes-hut", "speedup", "error"
+    );
+    for count in [250, 500, 1000, 2000, 4000, 8000] {
+        let (points, masses): (Vec<Vec2>, Vec<f32>) = (0..count)
+            .map(|_| {
+                // a disc, as in a galaxy toy
+                let radius = 100. * rng.gen::<f32>().sqrt();
+                let angle = rng.gen_range(0.0..std::f32::consts::TAU);
+                (Vec2::from_angle(angle) * radius, rng.gen_range(0.5..2.))
+            })
+            .unzip();
+        let settings = MutualGravitySettings::default();
+        let (exact, exact_time) = time(|| exact_accelerations(&points, &masses, &settings));
+
+        for opening_angle in [0.3, 0.5, 0.8] {
+            let settings = MutualGravitySettings {
+                opening_angle,
+                ..settings
+            };
+            let (approximate, approximate_time) =
+                time(|| barnes_hut_accelerations(&points, &masses, &settings));
+            let error: f32 = approximate
+                .iter()
+                .zip(&exact)
+                .map(|(approximate, exact)| (*approximate - *exact).length())
+                .sum::<f32>()
+                / exact.iter().map(|exact| exact.length()).sum::<f32>();
+            println!(
+                "{:>6} {:>6.1} {:>12.2?} {:>12.2?} {:>7.1}x {:>9.3}%",
+                count,
+                opening_angle,
+                exact_time,
+                approximate_time,
+                exact_time.as_secs_f64() / approximate_time.as_secs_f64(),
+                100. * error,
+            );
+        }
+    }
+}
@@ -1,15 +1,18 @@
+use std::time::Duration;
+
 use bevy::{
-    app::{App, FixedUpdate, Startup, Update},
+    app::{App, Startup, Update},
     asset::{Assets, Handle},
     core_pipeline::core_3d::Camera3dBundle,
     ecs::{
         entity::Entity,
+        schedule::IntoSystemConfigs,
         system::{Commands, Query, Res, ResMut, Resource},
     },
     math::{primitives::Rectangle, Vec2, Vec3},
     pbr::{PbrBundle, StandardMaterial},
     render::{camera::ClearColor, color::Color, mesh::Mesh, view::Msaa},
-    time::{Fixed, Time},
+    time::common_conditions::on_timer,
     transform::components::Transform,
     DefaultPlugins,
 };
@@ -21,10 +24,12 @@ fn main() {
         .insert_resource(ClearColor(Color::rgb(0.8, 0.8, 0.9)))
         .insert_resource(Msaa::Sample4)
         .add_plugins(DefaultPlugins)
-        .add_plugins(XPBDPlugin)
+        .add_plugins(XPBDPlugin::default())
         .add_systems(Startup, startup)
-        .insert_resource(Time::<Fixed>::from_seconds(1. / 2.))
-        .add_systems(FixedUpdate, spawn_boxes)
+        .add_systems(
+            Update,
+            spawn_boxes.run_if(on_timer(Duration::from_secs_f32(1. / 2.))),
+        )
         .add_systems(Update, despawn_boxes)
         .add_systems(Update, bevy::window::close_on_esc)
         .run();
@@ -0,0 +1,148 @@
+use bevy::{
+    app::{App, Startup, Update},
+    asset::Assets,
+    core_pipeline::core_3d::Camera3dBundle,
+    ecs::system::{Commands, ResMut},
+    math::{
+        primitives::{Rectangle, Sphere},
+        Vec2, Vec3,
+    },
+    pbr::{AlphaMode, PbrBundle, StandardMaterial},
+    render::{
+        camera::ClearColor,
+        color::Color,
+        mesh::{Mesh, Meshable},
+        view::Msaa,
+    },
+    transform::components::Transform,
+    DefaultPlugins,
+};
+use bevy_xpbd_tutorial::{
+    BoxCollider, CircleCollider, Density, DragPlugin, DynamicBoxBundle, FluidVolume,
+    ParticleBundle, Pos, StaticBoxBundle, XPBDPlugin,
+};
+
+fn main() {
+    App::new()
+        .insert_resource(ClearColor(Color::rgb(0.8, 0.8, 0.9)))
+        .insert_resource(Msaa::Sample4)
+        .add_plugins(DefaultPlugins)
+        .add_plugins(XPBDPlugin::default())
+        .add_plugins(DragPlugin::default())
+        .add_systems(Startup, startup)
+        .add_systems(Update, bevy::window::close_on_esc)
+        .run();
+}
+
+fn startup(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+    let grey = materials.add(StandardMaterial {
+        base_color: Color::rgb(0.4, 0.4, 0.6),
+        unlit: true,
+        ..Default::default()
+    });
+    let water = materials.add(StandardMaterial {
+        base_color: Color::rgba(0.2, 0.4, 0.9, 0.4),
+        alpha_mode: AlphaMode::Blend,
+        unlit: true,
+        ..Default::default()
+    });
+    let wood = materials.add(StandardMaterial {
+        base_color: Color::rgb(0.6, 0.4, 0.2),
+        unlit: true,
+        ..Default::default()
+    });
+    let quad = meshes.add(Mesh::from(Rectangle::from_size(Vec2::ONE)));
+    let sphere = meshes.add(Sphere::new(1.).mesh().ico(4).unwrap());
+
+    // tank floor and walls
+    for (pos, size) in [
+        (Vec2::new(0., -4.), Vec2::new(10., 0.5)),
+        (Vec2::new(-5., -1.5), Vec2::new(0.5, 5.5)),
+        (Vec2::new(5., -1.5), Vec2::new(0.5, 5.5)),
+    ] {
+        commands
+            .spawn(PbrBundle {
+                mesh: quad.clone(),
+                material: grey.clone(),
+                transform: Transform::from_scale(size.extend(1.)),
+                ..Default::default()
+            })
+            .insert(StaticBoxBundle {
+                pos: Pos(pos),
+                collider: BoxCollider { size },
+                ..Default::default()
+            });
+    }
+
+    let water_size = Vec2::new(9.5, 3.);
+    let water_pos = Vec2::new(0., -2.25);
+    commands.spawn((
+        PbrBundle {
+            mesh: quad.clone(),
+            material: water,
+            transform: Transform::from_scale(water_size.extend(1.)),
+            ..Default::default()
+        },
+        Pos(water_pos),
+        FluidVolume {
+            size: water_size,
+            flow_velocity: Vec2::new(0.3, 0.),
+            ..Default::default()
+        },
+    ));
+
+    // crates lighter than water float, the last one sinks
+    for (i, density) in [300., 500., 800., 1500.].into_iter().enumerate() {
+        let size = Vec2::splat(0.6);
+        let pos = Vec2::new(-3. + 1.5 * i as f32, 1.);
+        commands
+            .spawn(PbrBundle {
+                mesh: quad.clone(),
+                material: wood.clone(),
+                transform: Transform::from_scale(size.extend(1.)),
+                ..Default::default()
+            })
+            .insert((
+                DynamicBoxBundle {
+                    collider: BoxCollider { size },
+                    ..DynamicBoxBundle::new_with_pos_and_vel(pos, Vec2::ZERO)
+                },
+                Density(density),
+            ));
+    }
+
+    // bobbing marbles
+    for i in 0..6 {
+        let radius = 0.15;
+        let pos = Vec2::new(-3.5 + 1.4 * i as f32, 2.5);
+        commands
+            .spawn(PbrBundle {
+                mesh: sphere.clone(),
+                material: grey.clone(),
+                transform: Transform::from_scale(Vec3::splat(radius)),
+                ..Default::default()
+            })
+            .insert((
+                ParticleBundle {
+                    collider: CircleCollider { radius },
+                    ..ParticleBundle::new_with_pos_and_vel(pos, Vec2::ZERO)
+                },
+                Density(600.),
+            ));
+    }
+
+    commands.spawn(Camera3dBundle {
+        transform: Transform::from_translation(Vec3::new(0., 0., 100.)),
+        projection: bevy::render::camera::Projection::Orthographic(
+            bevy::render::camera::OrthographicProjection {
+                scale: 0.01,
+                ..Default::default()
+            },
+        ),
+        ..Camera3dBundle::default()
+    });
+}
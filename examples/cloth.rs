@@ -0,0 +1,85 @@
+use bevy::{
+    app::{App, Startup, Update},
+    asset::Assets,
+    core_pipeline::core_3d::Camera3dBundle,
+    ecs::{
+        event::EventReader,
+        system::{Commands, ResMut},
+    },
+    log::info,
+    math::{Vec2, Vec3},
+    pbr::{PbrBundle, StandardMaterial},
+    render::{
+        camera::ClearColor,
+        color::Color,
+        mesh::Mesh,
+        view::{Msaa, NoFrustumCulling},
+    },
+    transform::components::Transform,
+    DefaultPlugins,
+};
+use bevy_xpbd_tutorial::{ClothBuilder, ConstraintTorn, DeformableMesh, DragPlugin, XPBDPlugin};
+
+fn main() {
+    App::new()
+        .insert_resource(ClearColor(Color::rgb(0.8, 0.8, 0.9)))
+        .insert_resource(Msaa::Sample4)
+        .add_plugins(DefaultPlugins)
+        .add_plugins(XPBDPlugin::default())
+        .add_plugins(DragPlugin { compliance: 0.0001 })
+        .add_systems(Startup, startup)
+        .add_systems(Update, log_tears)
+        .add_systems(Update, bevy::window::close_on_esc)
+        .run();
+}
+
+fn startup(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+    let red = materials.add(StandardMaterial {
+        base_color: Color::rgb(0.8, 0.3, 0.3),
+        unlit: true,
+        double_sided: true,
+        cull_mode: None,
+        ..Default::default()
+    });
+
+    let builder = ClothBuilder::new(Vec2::new(-2.5, -1.), 21, 13, 0.25)
+        .with_particle_mass(0.05)
+        .with_max_strain(0.6);
+    let mesh = meshes.add(builder.mesh());
+    let cloth = builder.spawn(&mut commands);
+    commands.spawn((
+        PbrBundle {
+            mesh,
+            material: red,
+            ..Default::default()
+        },
+        DeformableMesh {
+            particles: cloth.particles,
+        },
+        NoFrustumCulling,
+    ));
+
+    commands.spawn(Camera3dBundle {
+        transform: Transform::from_translation(Vec3::new(0., 0., 100.)),
+        projection: bevy::render::camera::Projection::Orthographic(
+            bevy::render::camera::OrthographicProjection {
+                scale: 0.01,
+                ..Default::default()
+            },
+        ),
+        ..Camera3dBundle::default()
+    });
+}
+
+fn log_tears(mut torn: EventReader<ConstraintTorn>) {
+    for event in torn.read() {
+        info!(
+            "Cloth tore between {:?} and {:?}",
+            event.entity_a, event.entity_b
+        );
+    }
+}
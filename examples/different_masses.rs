@@ -72,7 +72,7 @@ fn main() {
         .insert_resource(ClearColor(Color::BLACK))
         .insert_resource(Msaa::Sample4)
         .add_plugins(DefaultPlugins)
-        .add_plugins(XPBDPlugin)
+        .add_plugins(XPBDPlugin::default())
         .insert_resource(Gravity(Vec2::ZERO))
         .add_systems(Startup, startup)
         .add_systems(Update, bevy::window::close_on_esc)
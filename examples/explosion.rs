@@ -0,0 +1,111 @@
+use bevy::{
+    app::{App, Startup, Update},
+    asset::Assets,
+    core_pipeline::core_3d::Camera3dBundle,
+    ecs::system::{Commands, Res, ResMut},
+    input::{keyboard::KeyCode, ButtonInput},
+    math::{primitives::Rectangle, Vec2, Vec3},
+    pbr::{PbrBundle, StandardMaterial},
+    render::{camera::ClearColor, color::Color, mesh::Mesh, view::Msaa},
+    transform::components::Transform,
+    DefaultPlugins,
+};
+use bevy_xpbd_tutorial::{
+    BoxCollider, DynamicBoxBundle, Explosion, Falloff, Pos, StaticBoxBundle, XPBDPlugin,
+};
+
+fn main() {
+    App::new()
+        .insert_resource(ClearColor(Color::rgb(0.8, 0.8, 0.9)))
+        .insert_resource(Msaa::Sample4)
+        .add_plugins(DefaultPlugins)
+        .add_plugins(XPBDPlugin::default())
+        .add_systems(Startup, startup)
+        .add_systems(Update, detonate)
+        .add_systems(Update, bevy::window::close_on_esc)
+        .run();
+}
+
+fn startup(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+    let blue = materials.add(StandardMaterial {
+        base_color: Color::rgb(0.4, 0.4, 0.6),
+        unlit: true,
+        ..Default::default()
+    });
+    let red = materials.add(StandardMaterial {
+        base_color: Color::rgb(0.8, 0.3, 0.3),
+        unlit: true,
+        ..Default::default()
+    });
+
+    let quad = meshes.add(Mesh::from(Rectangle::from_size(Vec2::ONE)));
+
+    // floor, and a wall sheltering the stack on the right
+    for (pos, size) in [
+        (Vec2::new(0., -3.), Vec2::new(12., 2.)),
+        (Vec2::new(1., -1.), Vec2::new(0.3, 2.)),
+    ] {
+        commands
+            .spawn(PbrBundle {
+                mesh: quad.clone(),
+                material: blue.clone(),
+                transform: Transform::from_scale(size.extend(1.)),
+                ..Default::default()
+            })
+            .insert(StaticBoxBundle {
+                pos: Pos(pos),
+                collider: BoxCollider { size },
+                ..Default::default()
+            });
+    }
+
+    let size = Vec2::splat(0.4);
+    for x in [-3., -2.5, 2.5, 3.] {
+        for row in 0..4 {
+            let pos = Vec2::new(x, -1.8 + row as f32 * size.y);
+            commands
+                .spawn(PbrBundle {
+                    mesh: quad.clone(),
+                    material: red.clone(),
+                    transform: Transform {
+                        scale: size.extend(1.),
+                        translation: pos.extend(0.),
+                        ..Default::default()
+                    },
+                    ..Default::default()
+                })
+                .insert(DynamicBoxBundle {
+                    collider: BoxCollider { size },
+                    ..DynamicBoxBundle::new_with_pos_and_vel(pos, Vec2::ZERO)
+                });
+        }
+    }
+
+    commands.spawn(Camera3dBundle {
+        transform: Transform::from_translation(Vec3::new(0., 0., 100.)),
+        projection: bevy::render::camera::Projection::Orthographic(
+            bevy::render::camera::OrthographicProjection {
+                scale: 0.01,
+                ..Default::default()
+            },
+        ),
+        ..Camera3dBundle::default()
+    });
+}
+
+/// space sets off a blast between the stacks, which the wall shields the right hand stack from
+fn detonate(mut commands: Commands, keys: Res<ButtonInput<KeyCode>>) {
+    if keys.just_pressed(KeyCode::Space) {
+        commands.add(Explosion {
+            center: Vec2::new(0., -1.5),
+            radius: 5.,
+            impulse: 10.,
+            falloff: Falloff::Linear,
+            occluded_by_statics: true,
+        });
+    }
+}
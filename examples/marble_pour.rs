@@ -1,9 +1,12 @@
+use std::time::Duration;
+
 use bevy::{
-    app::{App, FixedUpdate, Startup, Update},
+    app::{App, Startup, Update},
     asset::{Assets, Handle},
     core_pipeline::core_3d::Camera3dBundle,
     ecs::{
         entity::Entity,
+        schedule::IntoSystemConfigs,
         system::{Commands, Query, Res, ResMut, Resource},
     },
     math::{
@@ -17,7 +20,7 @@ use bevy::{
         mesh::{Mesh, Meshable},
         view::Msaa,
     },
-    time::{Fixed, Time},
+    time::common_conditions::on_timer,
     transform::components::Transform,
     DefaultPlugins,
 };
@@ -31,10 +34,12 @@ fn main() {
         .insert_resource(ClearColor(Color::rgb(0.8, 0.8, 0.9)))
         .insert_resource(Msaa::Sample4)
         .add_plugins(DefaultPlugins)
-        .add_plugins(XPBDPlugin)
+        .add_plugins(XPBDPlugin::default())
         .add_systems(Startup, startup)
-        .insert_resource(Time::<Fixed>::from_seconds(1. / 20.))
-        .add_systems(FixedUpdate, spawn_marbles)
+        .add_systems(
+            Update,
+            spawn_marbles.run_if(on_timer(Duration::from_secs_f32(1. / 20.))),
+        )
         .add_systems(Update, despawn_marbles)
         .add_systems(Update, bevy::window::close_on_esc)
         .run();
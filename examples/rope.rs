@@ -0,0 +1,108 @@
+use bevy::{
+    app::{App, Startup, Update},
+    asset::Assets,
+    core_pipeline::core_3d::Camera3dBundle,
+    ecs::system::{Commands, ResMut},
+    math::{primitives::Sphere, Vec2, Vec3},
+    pbr::{PbrBundle, StandardMaterial},
+    render::{
+        camera::ClearColor,
+        color::Color,
+        mesh::{Mesh, Meshable},
+        view::Msaa,
+    },
+    transform::components::Transform,
+    DefaultPlugins,
+};
+use bevy_xpbd_tutorial::{
+    CircleCollider, DragPlugin, Mass, ParticleBundle, Pos, RopeBuilder, StaticCircleBundle,
+    XPBDPlugin,
+};
+
+fn main() {
+    App::new()
+        .insert_resource(ClearColor(Color::rgb(0.8, 0.8, 0.9)))
+        .insert_resource(Msaa::Sample4)
+        .add_plugins(DefaultPlugins)
+        .add_plugins(XPBDPlugin::default())
+        .add_plugins(DragPlugin::default())
+        .add_systems(Startup, startup)
+        .add_systems(Update, bevy::window::close_on_esc)
+        .run();
+}
+
+fn startup(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+    let sphere = meshes.add(Sphere::new(1.).mesh().ico(4).unwrap());
+    let blue = materials.add(StandardMaterial {
+        base_color: Color::rgb(0.4, 0.4, 0.6),
+        unlit: true,
+        ..Default::default()
+    });
+
+    let anchor_pos = Vec2::new(0., 2.);
+    let anchor_radius = 0.1;
+    let anchor = commands
+        .spawn(PbrBundle {
+            mesh: sphere.clone(),
+            material: blue.clone(),
+            transform: Transform::from_scale(Vec3::splat(anchor_radius)),
+            ..Default::default()
+        })
+        .insert(StaticCircleBundle {
+            pos: Pos(anchor_pos),
+            collider: CircleCollider {
+                radius: anchor_radius,
+            },
+            ..Default::default()
+        })
+        .id();
+
+    let weight_pos = Vec2::new(3., 2.);
+    let weight_radius = 0.12;
+    let weight = commands
+        .spawn(PbrBundle {
+            mesh: sphere.clone(),
+            material: blue.clone(),
+            transform: Transform::from_scale(Vec3::splat(weight_radius)),
+            ..Default::default()
+        })
+        .insert(ParticleBundle {
+            mass: Mass(2.),
+            collider: CircleCollider {
+                radius: weight_radius,
+            },
+            ..ParticleBundle::new_with_pos_and_vel(weight_pos, Vec2::ZERO)
+        })
+        .id();
+
+    let particle_radius = 0.05;
+    let rope = RopeBuilder::new(anchor_pos, weight_pos)
+        .with_start_anchor(anchor)
+        .with_end_anchor(weight)
+        .with_segments(15)
+        .with_particle_radius(particle_radius)
+        .spawn(&mut commands);
+    for particle in rope.particles {
+        commands.entity(particle).insert(PbrBundle {
+            mesh: sphere.clone(),
+            material: blue.clone(),
+            transform: Transform::from_scale(Vec3::splat(particle_radius)),
+            ..Default::default()
+        });
+    }
+
+    commands.spawn(Camera3dBundle {
+        transform: Transform::from_translation(Vec3::new(0., 0., 100.)),
+        projection: bevy::render::camera::Projection::Orthographic(
+            bevy::render::camera::OrthographicProjection {
+                scale: 0.01,
+                ..Default::default()
+            },
+        ),
+        ..Camera3dBundle::default()
+    });
+}
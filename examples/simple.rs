@@ -60,7 +60,7 @@ fn main() {
         .insert_resource(Msaa::Sample4)
         .add_plugins(DefaultPlugins)
         .add_systems(Startup, startup)
-        .add_plugins(XPBDPlugin)
+        .add_plugins(XPBDPlugin::default())
         .add_systems(Update, bevy::window::close_on_esc)
         .run();
 }
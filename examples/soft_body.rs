@@ -0,0 +1,145 @@
+use std::f32::consts::TAU;
+
+use bevy::{
+    app::{App, Startup, Update},
+    asset::{Assets, Handle},
+    core_pipeline::core_3d::Camera3dBundle,
+    ecs::system::{Commands, Query, Res, ResMut},
+    input::{keyboard::KeyCode, ButtonInput},
+    math::{primitives::Rectangle, Vec2, Vec3},
+    pbr::{PbrBundle, StandardMaterial},
+    render::{
+        camera::ClearColor,
+        color::Color,
+        mesh::Mesh,
+        view::{Msaa, NoFrustumCulling},
+    },
+    transform::components::Transform,
+    DefaultPlugins,
+};
+use bevy_xpbd_tutorial::{
+    AreaConstraint, BoxCollider, DeformableMesh, DragPlugin, Pos, SoftBodyBuilder, StaticBoxBundle,
+    XPBDPlugin,
+};
+
+fn main() {
+    App::new()
+        .insert_resource(ClearColor(Color::rgb(0.8, 0.8, 0.9)))
+        .insert_resource(Msaa::Sample4)
+        .add_plugins(DefaultPlugins)
+        .add_plugins(XPBDPlugin::default())
+        .add_plugins(DragPlugin::default())
+        .add_systems(Startup, startup)
+        .add_systems(Update, inflate_balloons)
+        .add_systems(Update, bevy::window::close_on_esc)
+        .run();
+}
+
+fn spawn_soft_body(
+    commands: &mut Commands,
+    meshes: &mut Assets<Mesh>,
+    material: Handle<StandardMaterial>,
+    builder: SoftBodyBuilder,
+) {
+    let mesh = meshes.add(builder.mesh());
+    let soft_body = builder.spawn(commands);
+    commands.spawn((
+        PbrBundle {
+            mesh,
+            material,
+            ..Default::default()
+        },
+        DeformableMesh {
+            particles: soft_body.particles,
+        },
+        NoFrustumCulling,
+    ));
+}
+
+fn startup(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+    let blue = materials.add(StandardMaterial {
+        base_color: Color::rgb(0.4, 0.4, 0.6),
+        unlit: true,
+        ..Default::default()
+    });
+    let red = materials.add(StandardMaterial {
+        base_color: Color::rgb(0.8, 0.3, 0.3),
+        unlit: true,
+        ..Default::default()
+    });
+
+    let size = Vec2::new(10., 1.);
+    commands
+        .spawn(PbrBundle {
+            mesh: meshes.add(Mesh::from(Rectangle::from_size(Vec2::ONE))),
+            material: blue.clone(),
+            transform: Transform::from_scale(size.extend(1.)),
+            ..Default::default()
+        })
+        .insert(StaticBoxBundle {
+            pos: Pos(Vec2::new(0., -3.)),
+            collider: BoxCollider { size },
+            ..Default::default()
+        });
+
+    spawn_soft_body(
+        &mut commands,
+        &mut meshes,
+        red.clone(),
+        SoftBodyBuilder::grid(Vec2::new(-3., 0.), 6, 5, 0.3),
+    );
+
+    let outline: Vec<Vec2> = (0..16)
+        .map(|i| Vec2::from_angle(i as f32 / 16. * TAU) * 0.8 + Vec2::new(2., 1.))
+        .collect();
+    spawn_soft_body(
+        &mut commands,
+        &mut meshes,
+        red.clone(),
+        SoftBodyBuilder::polygon(&outline).with_shear_compliance(0.001),
+    );
+
+    let outline: Vec<Vec2> = outline.iter().map(|point| *point + Vec2::Y * 2.).collect();
+    spawn_soft_body(
+        &mut commands,
+        &mut meshes,
+        red.clone(),
+        SoftBodyBuilder::polygon(&outline).with_shape_matching(0.0001),
+    );
+
+    spawn_soft_body(
+        &mut commands,
+        &mut meshes,
+        red,
+        SoftBodyBuilder::balloon(Vec2::new(-2., 3.), 0.8, 24),
+    );
+
+    commands.spawn(Camera3dBundle {
+        transform: Transform::from_translation(Vec3::new(0., 0., 100.)),
+        projection: bevy::render::camera::Projection::Orthographic(
+            bevy::render::camera::OrthographicProjection {
+                scale: 0.01,
+                ..Default::default()
+            },
+        ),
+        ..Camera3dBundle::default()
+    });
+}
+
+/// up and down arrow keys inflate and deflate the balloon
+fn inflate_balloons(keys: Res<ButtonInput<KeyCode>>, mut query: Query<&mut AreaConstraint>) {
+    let factor = if keys.pressed(KeyCode::ArrowUp) {
+        1.01
+    } else if keys.pressed(KeyCode::ArrowDown) {
+        0.99
+    } else {
+        return;
+    };
+    for mut constraint in query.iter_mut() {
+        constraint.inflate(factor);
+    }
+}
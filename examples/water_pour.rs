@@ -0,0 +1,186 @@
+use std::time::Duration;
+
+use bevy::{
+    app::{App, Startup, Update},
+    asset::{Assets, Handle},
+    core_pipeline::core_3d::Camera3dBundle,
+    ecs::{
+        entity::Entity,
+        query::With,
+        schedule::IntoSystemConfigs,
+        system::{Commands, Query, Res, ResMut, Resource},
+    },
+    math::{
+        primitives::{Rectangle, Sphere},
+        Vec2, Vec3,
+    },
+    pbr::{PbrBundle, StandardMaterial},
+    render::{
+        camera::ClearColor,
+        color::Color,
+        mesh::{Mesh, Meshable},
+        view::Msaa,
+    },
+    time::common_conditions::on_timer,
+    transform::components::Transform,
+    DefaultPlugins,
+};
+use bevy_xpbd_tutorial::{
+    BoxCollider, CircleCollider, FluidConfig, FluidParticle, FluidParticleBundle, Pos,
+    StaticBoxBundle, XPBDPlugin,
+};
+use rand::random;
+
+const PARTICLE_SPACING: f32 = 0.1;
+const MAX_PARTICLES: usize = 1500;
+
+fn main() {
+    App::new()
+        .insert_resource(ClearColor(Color::rgb(0.8, 0.8, 0.9)))
+        .insert_resource(Msaa::Sample4)
+        .add_plugins(DefaultPlugins)
+        .add_plugins(XPBDPlugin::default())
+        .insert_resource(FluidConfig::from_particle_spacing(PARTICLE_SPACING))
+        .add_systems(Startup, startup)
+        .add_systems(
+            Update,
+            spawn_water.run_if(on_timer(Duration::from_secs_f32(1. / 20.))),
+        )
+        .add_systems(Update, despawn_water)
+        .add_systems(Update, bevy::window::close_on_esc)
+        .run();
+}
+
+#[derive(Debug, Resource)]
+struct Materials {
+    water: Handle<StandardMaterial>,
+}
+
+#[derive(Debug, Resource)]
+struct Meshes {
+    sphere: Handle<Mesh>,
+}
+
+fn spawn_static_box(
+    commands: &mut Commands,
+    mesh: Handle<Mesh>,
+    material: Handle<StandardMaterial>,
+    pos: Vec2,
+    size: Vec2,
+) {
+    commands
+        .spawn(PbrBundle {
+            mesh,
+            material,
+            transform: Transform::from_scale(size.extend(1.)),
+            ..Default::default()
+        })
+        .insert(StaticBoxBundle {
+            pos: Pos(pos),
+            collider: BoxCollider { size },
+            ..Default::default()
+        });
+}
+
+fn startup(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+    let sphere = meshes.add(Sphere::new(1.).mesh().ico(2).unwrap());
+    let quad = meshes.add(Mesh::from(Rectangle::from_size(Vec2::ONE)));
+    let grey = materials.add(StandardMaterial {
+        base_color: Color::rgb(0.4, 0.4, 0.5),
+        unlit: true,
+        ..Default::default()
+    });
+    let water = materials.add(StandardMaterial {
+        base_color: Color::rgb(0.2, 0.4, 0.9),
+        unlit: true,
+        ..Default::default()
+    });
+
+    // tank with a ledge for the water to spill off
+    spawn_static_box(
+        &mut commands,
+        quad.clone(),
+        grey.clone(),
+        Vec2::new(0., -3.),
+        Vec2::new(8., 0.5),
+    );
+    spawn_static_box(
+        &mut commands,
+        quad.clone(),
+        grey.clone(),
+        Vec2::new(-4., -1.5),
+        Vec2::new(0.5, 3.5),
+    );
+    spawn_static_box(
+        &mut commands,
+        quad.clone(),
+        grey.clone(),
+        Vec2::new(4., -1.5),
+        Vec2::new(0.5, 3.5),
+    );
+    spawn_static_box(
+        &mut commands,
+        quad,
+        grey,
+        Vec2::new(-1.5, 1.),
+        Vec2::new(3., 0.3),
+    );
+
+    commands.insert_resource(Meshes { sphere });
+    commands.insert_resource(Materials { water });
+
+    commands.spawn(Camera3dBundle {
+        transform: Transform::from_translation(Vec3::new(0., 0., 100.)),
+        projection: bevy::render::camera::Projection::Orthographic(
+            bevy::render::camera::OrthographicProjection {
+                scale: 0.01,
+                ..Default::default()
+            },
+        ),
+        ..Camera3dBundle::default()
+    });
+}
+
+fn spawn_water(
+    mut commands: Commands,
+    materials: Res<Materials>,
+    meshes: Res<Meshes>,
+    particles: Query<(), With<FluidParticle>>,
+) {
+    if particles.iter().count() >= MAX_PARTICLES {
+        return;
+    }
+    let radius = PARTICLE_SPACING / 2.;
+    for column in 0..5 {
+        let pos = Vec2::new(-2.5 + column as f32 * PARTICLE_SPACING, 3.)
+            + Vec2::new(random::<f32>() - 0.5, random::<f32>() - 0.5) * 0.01;
+        let vel = Vec2::new(1., -1.);
+        commands
+            .spawn(PbrBundle {
+                mesh: meshes.sphere.clone(),
+                material: materials.water.clone(),
+                transform: Transform {
+                    scale: Vec3::splat(radius),
+                    translation: pos.extend(0.),
+                    ..Default::default()
+                },
+                ..Default::default()
+            })
+            .insert(FluidParticleBundle {
+                collider: CircleCollider { radius },
+                ..FluidParticleBundle::new_with_pos_and_vel(pos, vel)
+            });
+    }
+}
+
+fn despawn_water(mut commands: Commands, query: Query<(Entity, &Pos), With<FluidParticle>>) {
+    for (entity, pos) in query.iter() {
+        if pos.0.y < -20. {
+            commands.entity(entity).despawn();
+        }
+    }
+}
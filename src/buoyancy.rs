@@ -0,0 +1,205 @@
+use bevy::{
+    ecs::{
+        component::Component,
+        system::{Query, Res},
+    },
+    math::Vec2,
+};
+
+use crate::{
+    BoxCollider, CircleCollider, Gravity, GravityOverride, GravityScale, InvMass, PhysicsTime, Pos,
+    RigidBody, Vel,
+};
+
+/// Box shaped body of liquid centred on its `Pos`, with its surface along the top edge. Dynamic
+/// bodies inside float by Archimedes' principle, according to the area under the surface.
+#[derive(Component, Clone, Copy, Debug)]
+pub struct FluidVolume {
+    pub size: Vec2,
+    /// Mass per unit area, 1000 for water alongside bodies with a `Density`
+    pub density: f32,
+    /// Rate per second at which fully submerged bodies take on the flow velocity
+    pub linear_drag: f32,
+    /// Velocity of the current, which submerged bodies are dragged towards
+    pub flow_velocity: Vec2,
+}
+
+impl Default for FluidVolume {
+    fn default() -> Self {
+        Self {
+            size: Vec2::ONE,
+            density: 1000.,
+            linear_drag: 1.,
+            flow_velocity: Vec2::ZERO,
+        }
+    }
+}
+
+/// Area of a circle of `radius` lying below a horizontal line `height` above its centre
+fn circle_area_below(radius: f32, height: f32) -> f32 {
+    let height = height.clamp(-radius, radius);
+    radius * radius * (-height / radius).acos()
+        + height * (radius * radius - height * height).sqrt()
+}
+
+/// Area of a circle inside an axis aligned box. Exact for the top and bottom edges, the sides
+/// are approximated by the overlapping fraction of the circle's width.
+fn circle_area_in_box(centre: Vec2, radius: f32, min: Vec2, max: Vec2) -> f32 {
+    let vertical =
+        circle_area_below(radius, max.y - centre.y) - circle_area_below(radius, min.y - centre.y);
+    let overlap = (centre.x + radius).min(max.x) - (centre.x - radius).max(min.x);
+    vertical * (overlap / (2. * radius)).clamp(0., 1.)
+}
+
+fn box_area_in_box(centre: Vec2, size: Vec2, min: Vec2, max: Vec2) -> f32 {
+    let overlap = (centre + size / 2.).min(max) - (centre - size / 2.).max(min);
+    overlap.max(Vec2::ZERO).x * overlap.max(Vec2::ZERO).y
+}
+
+#[allow(clippy::type_complexity)]
+pub(crate) fn apply_buoyancy(
+    volumes: Query<(&Pos, &FluidVolume)>,
+    mut bodies: Query<(
+        &Pos,
+        &mut Vel,
+        &InvMass,
+        &RigidBody,
+        Option<&CircleCollider>,
+        Option<&BoxCollider>,
+        Option<&GravityOverride>,
+        Option<&GravityScale>,
+    )>,
+    gravity: Res<Gravity>,
+    time: Res<PhysicsTime>,
+) {
+    let sub_dt = time.substep_delta_seconds();
+    for (volume_pos, volume) in volumes.iter() {
+        let min = volume_pos.0 - volume.size / 2.;
+        let max = volume_pos.0 + volume.size / 2.;
+        for (pos, mut vel, inv_mass, rigid_body, circle, r#box, gravity_override, gravity_scale) in
+            bodies.iter_mut()
+        {
+            if !rigid_body.is_dynamic() {
+                continue;
+            }
+            let (submerged, area) = match (circle, r#box) {
+                (Some(circle), _) => (
+                    circle_area_in_box(pos.0, circle.radius, min, max),
+                    circle.area(),
+                ),
+                (None, Some(r#box)) => (box_area_in_box(pos.0, r#box.size, min, max), r#box.area()),
+                (None, None) => continue,
+            };
+            if submerged <= 0. {
+                continue;
+            }
+            // weight of the displaced fluid, under the same gravity as the body
+            let gravity = gravity.for_body(gravity_override, gravity_scale);
+            vel.0 -= sub_dt * gravity * volume.density * submerged * inv_mass.0;
+            let fraction = submerged / area;
+            let drag = 1. - (-volume.linear_drag * fraction * sub_dt).exp();
+            let relative_flow = volume.flow_velocity - vel.0;
+            vel.0 += relative_flow * drag;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f32::consts::PI;
+
+    use bevy::{app::App, math::Vec2};
+
+    use super::{circle_area_below, circle_area_in_box, FluidVolume};
+    use crate::{
+        BoxCollider, Density, DynamicBoxBundle, GravityOverride, PhysicsSchedule, PhysicsWorld,
+        Pos, Vel, XPBDPlugin,
+    };
+
+    #[test]
+    fn circle_segment_areas() {
+        // arrange
+        let radius = 2.;
+
+        // act
+        let half = circle_area_below(radius, 0.);
+        let whole = circle_area_below(radius, 3.);
+        let none = circle_area_below(radius, -3.);
+        let inside = circle_area_in_box(Vec2::ZERO, radius, Vec2::splat(-5.), Vec2::new(5., 0.));
+
+        // assert
+        assert!((half - 2. * PI).abs() < 1e-5);
+        assert!((whole - 4. * PI).abs() < 1e-5);
+        assert_eq!(none, 0.);
+        assert!((inside - 2. * PI).abs() < 1e-5);
+    }
+
+    #[test]
+    fn crate_floats_half_submerged() {
+        // arrange
+        let mut app = App::new();
+        app.add_plugins(XPBDPlugin::new(PhysicsSchedule));
+        app.world.spawn((
+            Pos(Vec2::new(0., -5.)),
+            FluidVolume {
+                size: Vec2::new(20., 10.),
+                linear_drag: 2.,
+                ..Default::default()
+            },
+        ));
+        let crate_body = app
+            .world
+            .spawn((
+                DynamicBoxBundle {
+                    collider: BoxCollider { size: Vec2::ONE },
+                    ..DynamicBoxBundle::new_with_pos_and_vel(Vec2::new(0., 1.), Vec2::ZERO)
+                },
+                Density(500.),
+            ))
+            .id();
+
+        // act
+        for _ in 0..900 {
+            PhysicsWorld::step(&mut app.world);
+        }
+
+        // assert
+        // half of the unit box below the surface at y = 0
+        let height = app.world.get::<Pos>(crate_body).unwrap().0.y;
+        assert!(height.abs() < 0.02, "crate floating at {height}");
+    }
+
+    #[test]
+    fn weightless_body_is_not_pushed_up() {
+        // arrange
+        let mut app = App::new();
+        app.add_plugins(XPBDPlugin::new(PhysicsSchedule));
+        app.world.spawn((
+            Pos(Vec2::new(0., -5.)),
+            FluidVolume {
+                size: Vec2::new(20., 10.),
+                ..Default::default()
+            },
+        ));
+        let body = app
+            .world
+            .spawn((
+                DynamicBoxBundle {
+                    collider: BoxCollider { size: Vec2::ONE },
+                    ..DynamicBoxBundle::new_with_pos_and_vel(Vec2::new(0., -3.), Vec2::ZERO)
+                },
+                Density(500.),
+                GravityOverride(Vec2::ZERO),
+            ))
+            .id();
+
+        // act
+        for _ in 0..60 {
+            PhysicsWorld::step(&mut app.world);
+        }
+
+        // assert
+        assert_eq!(app.world.get::<Vel>(body).unwrap().get(), Vec2::ZERO);
+        assert_eq!(app.world.get::<Pos>(body).unwrap().0, Vec2::new(0., -3.));
+    }
+}
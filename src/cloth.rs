@@ -0,0 +1,77 @@
+use bevy::{ecs::system::Commands, math::Vec2, render::mesh::Mesh};
+
+use crate::{RigidBody, SoftBody, SoftBodyBuilder, Tearable};
+
+/// Builder for a sheet of cloth, such as a flag or a net: a grid of particles linked by distance
+/// constraints which can tear once stretched too far. Pair the mesh with a `DeformableMesh` to
+/// draw it.
+#[derive(Debug)]
+pub struct ClothBuilder {
+    lattice: SoftBodyBuilder,
+    columns: u32,
+    rows: u32,
+    pin_top_row: bool,
+    max_strain: Option<f32>,
+}
+
+impl ClothBuilder {
+    /// Cloth with its bottom left particle at `origin`
+    pub fn new(origin: Vec2, columns: u32, rows: u32, spacing: f32) -> Self {
+        let columns = columns.max(2);
+        let rows = rows.max(2);
+        Self {
+            lattice: SoftBodyBuilder::grid(origin, columns, rows, spacing)
+                .with_shear_compliance(0.001)
+                .with_bending_compliance(0.01),
+            columns,
+            rows,
+            pin_top_row: true,
+            max_strain: None,
+        }
+    }
+
+    /// Whether to hold the top row of particles in place as kinematic bodies, on by default
+    pub fn with_pinned_top_row(mut self, pin_top_row: bool) -> Self {
+        self.pin_top_row = pin_top_row;
+        self
+    }
+
+    /// Let links tear once stretched beyond this fraction of their rest length, sending a
+    /// `ConstraintTorn` event for each
+    pub fn with_max_strain(mut self, max_strain: f32) -> Self {
+        self.max_strain = Some(max_strain);
+        self
+    }
+
+    pub fn with_structural_compliance(mut self, compliance: f32) -> Self {
+        self.lattice = self.lattice.with_structural_compliance(compliance);
+        self
+    }
+
+    pub fn with_particle_mass(mut self, mass: f32) -> Self {
+        self.lattice = self.lattice.with_particle_mass(mass);
+        self
+    }
+
+    pub fn mesh(&self) -> Mesh {
+        self.lattice.mesh()
+    }
+
+    pub fn spawn(self, commands: &mut Commands) -> SoftBody {
+        let cloth = self.lattice.spawn(commands);
+
+        if self.pin_top_row {
+            let top_row = ((self.rows - 1) * self.columns) as usize;
+            for &particle in &cloth.particles[top_row..] {
+                commands.entity(particle).insert(RigidBody::Kinematic);
+            }
+        }
+        if let Some(max_strain) = self.max_strain {
+            for &constraint in &cloth.constraints {
+                commands.entity(constraint).insert(Tearable { max_strain });
+            }
+        }
+
+        cloth
+    }
+}
@@ -0,0 +1,129 @@
+use bevy::{
+    ecs::{
+        entity::Entity,
+        system::{EntityCommand, EntityCommands},
+        world::World,
+    },
+    math::Vec2,
+};
+
+use crate::{Aabb, Pos, PrevStepPos, Vel};
+
+/// Moves a body without giving it a velocity or leaving a streak when interpolating
+#[derive(Debug)]
+pub struct Teleport(pub Vec2);
+
+/// Sets a body's velocity, which the next step integrates from
+#[derive(Debug)]
+pub struct SetLinearVelocity(pub Vec2);
+
+/// `EntityCommands` for moving bodies directly rather than by writing `Pos` and `Vel`
+pub trait BodyCommandsExt {
+    fn teleport(&mut self, pos: Vec2) -> &mut Self;
+    fn set_linear_velocity(&mut self, vel: Vec2) -> &mut Self;
+}
+
+impl BodyCommandsExt for EntityCommands<'_> {
+    fn teleport(&mut self, pos: Vec2) -> &mut Self {
+        self.add(Teleport(pos))
+    }
+
+    fn set_linear_velocity(&mut self, vel: Vec2) -> &mut Self {
+        self.add(SetLinearVelocity(vel))
+    }
+}
+
+impl EntityCommand for Teleport {
+    fn apply(self, id: Entity, world: &mut World) {
+        let Some(mut entity) = world.get_entity_mut(id) else {
+            return;
+        };
+        let Some(mut pos) = entity.get_mut::<Pos>() else {
+            return;
+        };
+        let offset = self.0 - pos.0;
+        pos.0 = self.0;
+        // `PrevPos` is reset from `Pos` at the start of every substep, so only the position
+        // rendering blends from needs moving too
+        if let Some(mut prev_step_pos) = entity.get_mut::<PrevStepPos>() {
+            prev_step_pos.0 = self.0;
+        }
+        if let Some(mut aabb) = entity.get_mut::<Aabb>() {
+            aabb.min += offset;
+            aabb.max += offset;
+        }
+    }
+}
+
+impl EntityCommand for SetLinearVelocity {
+    fn apply(self, id: Entity, world: &mut World) {
+        let Some(mut entity) = world.get_entity_mut(id) else {
+            return;
+        };
+        if let Some(mut vel) = entity.get_mut::<Vel>() {
+            vel.0 = self.0;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::{ecs::system::EntityCommand, math::Vec2};
+
+    use super::{SetLinearVelocity, Teleport};
+    use crate::{
+        tests::zero_gravity_app, ParticleBundle, PhysicsConfig, PhysicsWorld, Pos, PrevStepPos, Vel,
+    };
+
+    #[test]
+    fn teleport_keeps_velocity() {
+        // arrange
+        let mut app = zero_gravity_app();
+        let body = app
+            .world
+            .spawn((
+                ParticleBundle::new_with_pos_and_vel(Vec2::ZERO, Vec2::new(1., 0.)),
+                PrevStepPos::default(),
+            ))
+            .id();
+        PhysicsWorld::step(&mut app.world);
+
+        // act
+        Teleport(Vec2::new(2., 1.)).apply(body, &mut app.world);
+        let prev_step_pos = app.world.get::<PrevStepPos>(body).unwrap().0;
+        PhysicsWorld::step(&mut app.world);
+
+        // assert
+        let pos = app.world.get::<Pos>(body).unwrap().0;
+        let vel = app.world.get::<Vel>(body).unwrap().get();
+        assert_eq!(prev_step_pos, Vec2::new(2., 1.));
+        // velocity is rebuilt from f32 positions every substep, so only close to the original
+        assert!(
+            (vel - Vec2::new(1., 0.)).length() < 1e-3,
+            "velocity became {vel}"
+        );
+        assert!(pos.distance(Vec2::new(2., 1.)) < 0.1);
+    }
+
+    #[test]
+    fn set_linear_velocity_applies_from_next_step() {
+        // arrange
+        let mut app = zero_gravity_app();
+        let body = app.world.spawn(ParticleBundle::default()).id();
+        PhysicsWorld::step(&mut app.world);
+
+        // act
+        SetLinearVelocity(Vec2::new(0., 2.)).apply(body, &mut app.world);
+        PhysicsWorld::step(&mut app.world);
+
+        // assert
+        let pos = app.world.get::<Pos>(body).unwrap().0;
+        let vel = app.world.get::<Vel>(body).unwrap().get();
+        let timestep = PhysicsConfig::default().timestep;
+        assert!((vel - Vec2::new(0., 2.)).length() < 1e-3, "velocity {vel}");
+        assert!(
+            pos.distance(Vec2::new(0., 2. * timestep)) < 1e-4,
+            "moved to {pos}"
+        );
+    }
+}
@@ -1,3 +1,9 @@
+use std::{
+    error::Error,
+    f32::consts::PI,
+    fmt::{self, Display},
+};
+
 use bevy::{ecs::component::Component, math::Vec2};
 
 /// Component for Axis-aligned bounding boxes
@@ -12,7 +18,7 @@ impl Aabb {
         self.max.x >= other.min.x
             && self.max.y >= other.min.y
             && self.min.x <= other.max.x
-            && self.min.y <= other.max.x
+            && self.min.y <= other.max.y
     }
 }
 
@@ -27,6 +33,17 @@ impl Default for BoxCollider {
     }
 }
 
+impl BoxCollider {
+    pub fn area(&self) -> f32 {
+        self.size.x * self.size.y
+    }
+
+    /// Moment of inertia about the centre for a body of the given mass
+    pub fn inertia(&self, mass: f32) -> f32 {
+        mass * self.size.length_squared() / 12.
+    }
+}
+
 #[derive(Component, Debug)]
 pub struct CircleCollider {
     pub radius: f32,
@@ -38,13 +55,30 @@ impl Default for CircleCollider {
     }
 }
 
+impl CircleCollider {
+    pub fn area(&self) -> f32 {
+        PI * self.radius * self.radius
+    }
+
+    /// Moment of inertia about the centre for a body of the given mass
+    pub fn inertia(&self, mass: f32) -> f32 {
+        mass * self.radius * self.radius / 2.
+    }
+}
+
 #[derive(Component, Debug, Default)]
 pub struct Pos(pub Vec2);
 
 #[derive(Component, Debug, Default)]
 pub struct PrevPos(pub Vec2);
 
-#[derive(Component, Debug)]
+/// Position at the start of the latest timestep, added automatically while `Interpolation` is
+/// enabled so that transforms can be blended between timesteps
+#[derive(Component, Debug, Default)]
+pub struct PrevStepPos(pub Vec2);
+
+/// Body mass in kilograms. Use `f32::INFINITY` for a dynamic body which cannot be pushed.
+#[derive(Component, Debug, PartialEq)]
 pub struct Mass(pub f32);
 
 impl Default for Mass {
@@ -53,6 +87,135 @@ impl Default for Mass {
     }
 }
 
+impl Mass {
+    pub fn try_new(kilograms: f32) -> Result<Self, MassError> {
+        if kilograms > 0. {
+            Ok(Self(kilograms))
+        } else {
+            Err(MassError(kilograms))
+        }
+    }
+}
+
+/// Returned for masses which are zero, negative or NaN
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct MassError(pub f32);
+
+impl Display for MassError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "mass must be greater than zero, got {}", self.0)
+    }
+}
+
+impl Error for MassError {}
+
+/// Mass per unit area in kg/m². When present, `Mass` is computed from this and the collider
+/// area.
+#[derive(Component, Debug)]
+pub struct Density(pub f32);
+
+/// Inverse of `Mass`, kept up to date by the plugin. Zero for infinite mass.
+#[derive(Component, Debug)]
+pub struct InvMass(pub(crate) f32);
+
+impl Default for InvMass {
+    fn default() -> Self {
+        Self(1.)
+    }
+}
+
+impl InvMass {
+    pub fn get(&self) -> f32 {
+        self.0
+    }
+}
+
+/// Moment of inertia about the centre of mass, computed from `Mass` and the collider shape
+#[derive(Component, Debug, Default)]
+pub struct Inertia(pub(crate) f32);
+
+impl Inertia {
+    pub fn get(&self) -> f32 {
+        self.0
+    }
+}
+
+/// How a body takes part in the simulation
+#[derive(Component, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum RigidBody {
+    /// Moved by gravity, contacts and constraints
+    #[default]
+    Dynamic,
+    /// Never moves
+    Static,
+    /// Moved only by its `Vel` or a `KinematicTarget`, pushing dynamic bodies aside as if it had
+    /// infinite mass
+    Kinematic,
+}
+
+impl RigidBody {
+    pub fn is_dynamic(self) -> bool {
+        self == Self::Dynamic
+    }
+
+    pub fn is_static(self) -> bool {
+        self == Self::Static
+    }
+
+    pub fn is_kinematic(self) -> bool {
+        self == Self::Kinematic
+    }
+
+    /// Inverse mass used by the solver, zero for static and kinematic bodies
+    pub(crate) fn inverse_mass(self, inv_mass: &InvMass) -> f32 {
+        if self.is_dynamic() {
+            inv_mass.0
+        } else {
+            0.
+        }
+    }
+}
+
+/// Position a kinematic body should reach by the end of the next physics step
+#[derive(Component, Debug)]
+pub struct KinematicTarget(pub Vec2);
+
+/// Force in newtons applied to a dynamic body every substep, until changed. Bodies do not
+/// rotate, so there is no torque counterpart.
+#[derive(Component, Debug, Default)]
+pub struct ExternalForce(pub Vec2);
+
+/// Impulse in newton seconds applied to a dynamic body once, at the start of the next physics
+/// step, after which it is reset to zero
+#[derive(Component, Debug, Default)]
+pub struct ExternalImpulse(pub Vec2);
+
+/// Multiplier on the gravity a dynamic body feels, such as 0 for a floating pickup or a negative
+/// value for a balloon
+#[derive(Component, Debug)]
+pub struct GravityScale(pub f32);
+
+impl Default for GravityScale {
+    fn default() -> Self {
+        Self(1.)
+    }
+}
+
+/// Gravity for this body in place of the global `Gravity` resource, still multiplied by any
+/// `GravityScale`
+#[derive(Component, Debug, Default)]
+pub struct GravityOverride(pub Vec2);
+
+/// Velocity proportional damping rate per second of a dynamic body, in place of
+/// `PhysicsConfig::linear_damping`
+#[derive(Component, Debug, Default)]
+pub struct LinearDamping(pub f32);
+
+/// Drag proportional to the square of a dynamic body's speed, as the deceleration per unit
+/// speed squared, in place of `PhysicsConfig::quadratic_drag`
+#[derive(Component, Debug, Default)]
+pub struct QuadraticDrag(pub f32);
+
 #[derive(Component, Debug)]
 pub struct Restitution(pub f32);
 
@@ -62,15 +225,39 @@ impl Default for Restitution {
     }
 }
 
+/// Velocity at the start of the current substep's velocity solve
 #[derive(Component, Debug, Default)]
 pub struct PreSolveVel(pub(crate) Vec2);
 
+impl PreSolveVel {
+    pub fn get(&self) -> Vec2 {
+        self.0
+    }
+}
+
+/// Linear velocity in metres per second
 #[derive(Component, Debug, Default)]
 pub struct Vel(pub(crate) Vec2);
 
+impl Vel {
+    pub fn new(vel: Vec2) -> Self {
+        Self(vel)
+    }
+
+    pub fn get(&self) -> Vec2 {
+        self.0
+    }
+
+    /// Overrides the velocity from the next substep on. Use `BodyCommandsExt` to also keep the
+    /// previous position in step.
+    pub fn set(&mut self, vel: Vec2) {
+        self.0 = vel;
+    }
+}
+
 #[cfg(test)]
 mod tests {
-    use super::CircleCollider;
+    use super::{CircleCollider, Mass};
     use float_cmp::approx_eq;
 
     #[test]
@@ -89,4 +276,13 @@ mod tests {
             ulps = 2
         ));
     }
+
+    #[test]
+    fn mass_rejects_non_positive_values() {
+        assert!(Mass::try_new(2.).is_ok());
+        assert!(Mass::try_new(f32::INFINITY).is_ok());
+        assert!(Mass::try_new(0.).is_err());
+        assert!(Mass::try_new(-1.).is_err());
+        assert!(Mass::try_new(f32::NAN).is_err());
+    }
 }
@@ -0,0 +1,389 @@
+use bevy::{
+    ecs::{
+        component::Component,
+        entity::Entity,
+        event::{Event, EventWriter},
+        system::{Commands, Query, Res},
+    },
+    math::Vec2,
+};
+
+use crate::{InvMass, PhysicsTime, Pos, RigidBody};
+
+/// Compliant constraint pulling a body towards a fixed point in world space.
+/// A compliance of zero gives a rigid pin, larger values give a softer spring.
+#[derive(Component, Debug)]
+pub struct PointConstraint {
+    pub target: Vec2,
+    pub compliance: f32,
+}
+
+impl PointConstraint {
+    pub fn new(target: Vec2, compliance: f32) -> Self {
+        Self { target, compliance }
+    }
+}
+
+/// Compliant constraint keeping two bodies at a fixed distance from each other. Spawn it on an
+/// entity of its own. Static and kinematic bodies act as immovable anchors.
+#[derive(Component, Debug)]
+pub struct DistanceConstraint {
+    pub entity_a: Entity,
+    pub entity_b: Entity,
+    pub rest_length: f32,
+    pub compliance: f32,
+}
+
+impl DistanceConstraint {
+    pub fn new(entity_a: Entity, entity_b: Entity, rest_length: f32, compliance: f32) -> Self {
+        Self {
+            entity_a,
+            entity_b,
+            rest_length,
+            compliance,
+        }
+    }
+}
+
+/// Makes a `DistanceConstraint` on the same entity break once it is stretched too far.
+/// `max_strain` is the extension as a fraction of the rest length, so 0.5 tears at 150 %.
+#[derive(Component, Debug)]
+pub struct Tearable {
+    pub max_strain: f32,
+}
+
+/// Sent when a tearable constraint breaks. The constraint entity has already been despawned.
+#[derive(Event, Debug)]
+pub struct ConstraintTorn {
+    pub constraint: Entity,
+    pub entity_a: Entity,
+    pub entity_b: Entity,
+}
+
+/// Shape matching constraint (Müller et al. 2005). Pulls a cluster of particles towards the
+/// best fit rigid transform of their rest shape, which makes for cheap and stable squishy blobs.
+/// Centres are computed with equal weights, so keep particle masses in a cluster similar.
+#[derive(Component, Debug)]
+pub struct ShapeMatchingConstraint {
+    pub particles: Vec<Entity>,
+    /// Rest position for each particle, in any frame of reference
+    pub rest_positions: Vec<Vec2>,
+    pub compliance: f32,
+}
+
+impl ShapeMatchingConstraint {
+    pub fn new(particles: Vec<Entity>, rest_positions: Vec<Vec2>, compliance: f32) -> Self {
+        assert_eq!(
+            particles.len(),
+            rest_positions.len(),
+            "shape matching needs one rest position per particle"
+        );
+        Self {
+            particles,
+            rest_positions,
+            compliance,
+        }
+    }
+}
+
+/// Pressure constraint keeping the area enclosed by a closed loop of particles close to a target,
+/// so inflatable bodies hold their volume when squashed. List the particles counter-clockwise.
+#[derive(Component, Debug)]
+pub struct AreaConstraint {
+    pub particles: Vec<Entity>,
+    /// Area to maintain, change it at runtime to inflate or deflate the body
+    pub target_area: f32,
+    pub compliance: f32,
+}
+
+impl AreaConstraint {
+    pub fn new(particles: Vec<Entity>, target_area: f32, compliance: f32) -> Self {
+        Self {
+            particles,
+            target_area,
+            compliance,
+        }
+    }
+
+    /// Scale the target area, values above one inflate and values below deflate
+    pub fn inflate(&mut self, factor: f32) {
+        self.target_area *= factor;
+    }
+}
+
+/// Area enclosed by a polygon, positive when the points run counter-clockwise
+pub(crate) fn signed_area(outline: &[Vec2]) -> f32 {
+    outline
+        .iter()
+        .zip(outline.iter().cycle().skip(1))
+        .map(|(a, b)| a.perp_dot(*b))
+        .sum::<f32>()
+        / 2.
+}
+
+/// Rotation, as a unit complex number, best mapping the centred rest offsets onto the centred
+/// current offsets
+fn best_fit_rotation(current: &[Vec2], rest: &[Vec2]) -> Vec2 {
+    let (dot_sum, cross_sum) = current
+        .iter()
+        .zip(rest)
+        .fold((0., 0.), |(dot_sum, cross_sum), (p, q)| {
+            (dot_sum + p.dot(*q), cross_sum + q.perp_dot(*p))
+        });
+    Vec2::new(dot_sum, cross_sum)
+        .try_normalize()
+        .unwrap_or(Vec2::X)
+}
+
+fn centroid(points: &[Vec2]) -> Vec2 {
+    points.iter().copied().sum::<Vec2>() / points.len() as f32
+}
+
+pub(crate) fn solve_point_constraints(
+    mut query: Query<(&mut Pos, &InvMass, &RigidBody, &PointConstraint)>,
+    time: Res<PhysicsTime>,
+) {
+    for (mut pos, inv_mass, rigid_body, constraint) in query.iter_mut() {
+        if !rigid_body.is_dynamic() {
+            continue;
+        }
+        let delta = pos.0 - constraint.target;
+        let length = delta.length();
+        if length <= f32::EPSILON {
+            continue;
+        }
+        let normal = delta / length;
+        let w = rigid_body.inverse_mass(inv_mass);
+        let alpha = constraint.compliance / time.substep_delta_seconds().powi(2);
+        let lambda = -length / (w + alpha);
+        pos.0 += normal * lambda * w;
+    }
+}
+
+pub(crate) fn solve_distance_constraints(
+    constraints: Query<&DistanceConstraint>,
+    mut bodies: Query<(&mut Pos, Option<&InvMass>, &RigidBody)>,
+    time: Res<PhysicsTime>,
+) {
+    for constraint in constraints.iter() {
+        let Ok([(mut pos_a, inv_mass_a, rigid_body_a), (mut pos_b, inv_mass_b, rigid_body_b)]) =
+            bodies.get_many_mut([constraint.entity_a, constraint.entity_b])
+        else {
+            continue;
+        };
+        let w_a = inv_mass_a.map_or(0., |inv_mass| rigid_body_a.inverse_mass(inv_mass));
+        let w_b = inv_mass_b.map_or(0., |inv_mass| rigid_body_b.inverse_mass(inv_mass));
+        let w_sum = w_a + w_b;
+        if w_sum <= 0. {
+            continue;
+        }
+
+        let delta = pos_b.0 - pos_a.0;
+        let length = delta.length();
+        if length <= f32::EPSILON {
+            continue;
+        }
+        let normal = delta / length;
+        let alpha = constraint.compliance / time.substep_delta_seconds().powi(2);
+        let lambda = -(length - constraint.rest_length) / (w_sum + alpha);
+        pos_a.0 -= normal * lambda * w_a;
+        pos_b.0 += normal * lambda * w_b;
+    }
+}
+
+pub(crate) fn tear_constraints(
+    mut commands: Commands,
+    constraints: Query<(Entity, &DistanceConstraint, &Tearable)>,
+    bodies: Query<&Pos>,
+    mut torn: EventWriter<ConstraintTorn>,
+) {
+    for (entity, constraint, tearable) in constraints.iter() {
+        let Ok([pos_a, pos_b]) = bodies.get_many([constraint.entity_a, constraint.entity_b]) else {
+            continue;
+        };
+        let strain = pos_a.0.distance(pos_b.0) / constraint.rest_length - 1.;
+        if strain > tearable.max_strain {
+            commands.entity(entity).despawn();
+            torn.send(ConstraintTorn {
+                constraint: entity,
+                entity_a: constraint.entity_a,
+                entity_b: constraint.entity_b,
+            });
+        }
+    }
+}
+
+pub(crate) fn solve_shape_matching_constraints(
+    constraints: Query<&ShapeMatchingConstraint>,
+    mut bodies: Query<(&mut Pos, &InvMass, &RigidBody)>,
+    time: Res<PhysicsTime>,
+) {
+    for constraint in constraints.iter() {
+        let mut entities = Vec::with_capacity(constraint.particles.len());
+        let mut current = Vec::with_capacity(constraint.particles.len());
+        let mut rest = Vec::with_capacity(constraint.particles.len());
+        let rest_positions = constraint.particles.iter().zip(&constraint.rest_positions);
+        for (&entity, &rest_position) in rest_positions {
+            if let Ok((pos, _, _)) = bodies.get(entity) {
+                entities.push(entity);
+                current.push(pos.0);
+                rest.push(rest_position);
+            }
+        }
+        if entities.is_empty() {
+            continue;
+        }
+
+        let centre = centroid(&current);
+        let rest_centre = centroid(&rest);
+        for position in &mut current {
+            *position -= centre;
+        }
+        for position in &mut rest {
+            *position -= rest_centre;
+        }
+        let rotation = best_fit_rotation(&current, &rest);
+
+        let alpha = constraint.compliance / time.substep_delta_seconds().powi(2);
+        for (entity, rest_offset) in entities.into_iter().zip(rest) {
+            let Ok((mut pos, inv_mass, rigid_body)) = bodies.get_mut(entity) else {
+                continue;
+            };
+            let w = rigid_body.inverse_mass(inv_mass);
+            if w <= 0. {
+                continue;
+            }
+            let goal = centre + rotation.rotate(rest_offset);
+            let offset = goal - pos.0;
+            pos.0 += offset * (w / (w + alpha));
+        }
+    }
+}
+
+pub(crate) fn solve_area_constraints(
+    constraints: Query<&AreaConstraint>,
+    mut bodies: Query<(&mut Pos, &InvMass, &RigidBody)>,
+    time: Res<PhysicsTime>,
+) {
+    for constraint in constraints.iter() {
+        let mut entities = Vec::with_capacity(constraint.particles.len());
+        let mut positions = Vec::with_capacity(constraint.particles.len());
+        let mut inverse_masses = Vec::with_capacity(constraint.particles.len());
+        for &entity in &constraint.particles {
+            if let Ok((pos, inv_mass, rigid_body)) = bodies.get(entity) {
+                entities.push(entity);
+                positions.push(pos.0);
+                inverse_masses.push(rigid_body.inverse_mass(inv_mass));
+            }
+        }
+        let count = positions.len();
+        if count < 3 {
+            continue;
+        }
+
+        let gradients: Vec<Vec2> = (0..count)
+            .map(|i| {
+                let next = positions[(i + 1) % count];
+                let previous = positions[(i + count - 1) % count];
+                -0.5 * (next - previous).perp()
+            })
+            .collect();
+        let w_sum: f32 = gradients
+            .iter()
+            .zip(&inverse_masses)
+            .map(|(gradient, w)| w * gradient.length_squared())
+            .sum();
+        let alpha = constraint.compliance / time.substep_delta_seconds().powi(2);
+        if w_sum + alpha <= 0. {
+            continue;
+        }
+
+        let c = signed_area(&positions) - constraint.target_area;
+        let lambda = -c / (w_sum + alpha);
+        for ((entity, gradient), w) in entities.into_iter().zip(gradients).zip(inverse_masses) {
+            if let Ok((mut pos, _, _)) = bodies.get_mut(entity) {
+                pos.0 += gradient * (lambda * w);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{best_fit_rotation, signed_area, ConstraintTorn, DistanceConstraint, Tearable};
+    use crate::{tests::zero_gravity_app, ParticleBundle, PhysicsWorld, RigidBody};
+    use bevy::{ecs::event::Events, math::Vec2};
+
+    #[test]
+    fn signed_area_is_positive_for_counter_clockwise_outline() {
+        // arrange
+        let outline = [
+            Vec2::ZERO,
+            Vec2::new(2., 0.),
+            Vec2::new(2., 1.),
+            Vec2::new(0., 1.),
+        ];
+
+        // act
+        let result = signed_area(&outline);
+
+        // assert
+        assert!((result - 2.).abs() < f32::EPSILON);
+    }
+
+    #[test]
+    fn best_fit_rotation_recovers_rotated_shape() {
+        // arrange
+        let rest = [
+            Vec2::new(-1., -0.5),
+            Vec2::new(1., -0.5),
+            Vec2::new(1., 0.5),
+            Vec2::new(-1., 0.5),
+        ];
+        let expected = Vec2::from_angle(0.7);
+        let current = rest.map(|position| expected.rotate(position));
+
+        // act
+        let result = best_fit_rotation(&current, &rest);
+
+        // assert
+        assert!((result - expected).length() < 1e-5);
+    }
+
+    #[test]
+    fn overstretched_tearable_constraint_is_despawned_once() {
+        // arrange
+        let mut app = zero_gravity_app();
+        let mut anchor = |x| {
+            app.world
+                .spawn(ParticleBundle {
+                    rigid_body: RigidBody::Static,
+                    ..ParticleBundle::new_with_pos_and_vel(Vec2::new(x, 0.), Vec2::ZERO)
+                })
+                .id()
+        };
+        let entity_a = anchor(0.);
+        let entity_b = anchor(2.);
+        let constraint = app
+            .world
+            .spawn((
+                DistanceConstraint::new(entity_a, entity_b, 1., 0.),
+                Tearable { max_strain: 0.5 },
+            ))
+            .id();
+
+        // act
+        for _ in 0..3 {
+            PhysicsWorld::step(&mut app.world);
+        }
+
+        // assert
+        assert!(app.world.get_entity(constraint).is_none());
+        let events = app.world.resource::<Events<ConstraintTorn>>();
+        let mut reader = events.get_reader();
+        let torn: Vec<_> = reader.read(events).collect();
+        assert_eq!(torn.len(), 1);
+        assert_eq!(torn[0].constraint, constraint);
+        assert_eq!((torn[0].entity_a, torn[0].entity_b), (entity_a, entity_b));
+    }
+}
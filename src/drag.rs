@@ -0,0 +1,199 @@
+use bevy::{
+    app::{App, Plugin, Update},
+    ecs::{
+        entity::Entity,
+        query::{With, Without},
+        schedule::IntoSystemConfigs,
+        system::{Commands, Query, Res, ResMut, Resource},
+    },
+    input::{mouse::MouseButton, ButtonInput},
+    math::Vec2,
+    render::camera::Camera,
+    transform::components::GlobalTransform,
+    window::{PrimaryWindow, Window},
+};
+
+use crate::{BoxCollider, CircleCollider, PointConstraint, Pos, RigidBody};
+
+/// Optional plugin for picking up bodies with the left mouse button and dragging them around
+/// on a soft spring. Expects a single camera looking down the z axis at the physics plane.
+/// Bodies which already have a `PointConstraint`, such as pinned ones, cannot be picked up.
+#[derive(Debug)]
+pub struct DragPlugin {
+    /// Compliance of the spring between the cursor and the dragged body
+    pub compliance: f32,
+}
+
+impl Default for DragPlugin {
+    fn default() -> Self {
+        Self { compliance: 0.001 }
+    }
+}
+
+/// Cursor position projected onto the physics plane, `None` when the cursor is outside the
+/// window
+#[derive(Debug, Default, Resource)]
+pub struct CursorWorldPos(pub Option<Vec2>);
+
+#[derive(Debug, Resource)]
+struct DragState {
+    compliance: f32,
+    entity: Option<Entity>,
+}
+
+impl Plugin for DragPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<CursorWorldPos>()
+            .insert_resource(DragState {
+                compliance: self.compliance,
+                entity: None,
+            })
+            .add_systems(
+                Update,
+                (update_cursor_world_pos, start_drag, update_drag, end_drag).chain(),
+            );
+    }
+}
+
+fn update_cursor_world_pos(
+    windows: Query<&Window, With<PrimaryWindow>>,
+    cameras: Query<(&Camera, &GlobalTransform)>,
+    mut cursor_world_pos: ResMut<CursorWorldPos>,
+) {
+    cursor_world_pos.0 = None;
+    let (Ok(window), Ok((camera, camera_transform))) = (windows.get_single(), cameras.get_single())
+    else {
+        return;
+    };
+    let Some(ray) = window
+        .cursor_position()
+        .and_then(|cursor| camera.viewport_to_world(camera_transform, cursor))
+    else {
+        return;
+    };
+
+    // intersect the ray with the z = 0 plane the bodies live in
+    let direction = *ray.direction;
+    let world_pos = if direction.z.abs() > f32::EPSILON {
+        ray.origin - direction * (ray.origin.z / direction.z)
+    } else {
+        ray.origin
+    };
+    cursor_world_pos.0 = Some(world_pos.truncate());
+}
+
+#[allow(clippy::type_complexity)]
+fn start_drag(
+    mut commands: Commands,
+    buttons: Res<ButtonInput<MouseButton>>,
+    cursor_world_pos: Res<CursorWorldPos>,
+    mut state: ResMut<DragState>,
+    bodies: Query<
+        (
+            Entity,
+            &Pos,
+            &RigidBody,
+            Option<&CircleCollider>,
+            Option<&BoxCollider>,
+        ),
+        // dragging would replace the body's own constraint and remove it on release
+        Without<PointConstraint>,
+    >,
+) {
+    if !buttons.just_pressed(MouseButton::Left) {
+        return;
+    }
+    let Some(cursor) = cursor_world_pos.0 else {
+        return;
+    };
+
+    let picked = bodies
+        .iter()
+        .find_map(|(entity, pos, rigid_body, circle, r#box)| {
+            if !rigid_body.is_dynamic() {
+                return None;
+            }
+            let offset = cursor - pos.0;
+            let hit = circle.is_some_and(|circle| offset.length_squared() <= circle.radius.powi(2))
+                || r#box.is_some_and(|r#box| {
+                    offset.x.abs() <= r#box.size.x / 2. && offset.y.abs() <= r#box.size.y / 2.
+                });
+            hit.then_some(entity)
+        });
+
+    if let Some(entity) = picked {
+        commands
+            .entity(entity)
+            .insert(PointConstraint::new(cursor, state.compliance));
+        state.entity = Some(entity);
+    }
+}
+
+fn update_drag(
+    cursor_world_pos: Res<CursorWorldPos>,
+    state: Res<DragState>,
+    mut constraints: Query<&mut PointConstraint>,
+) {
+    let (Some(entity), Some(cursor)) = (state.entity, cursor_world_pos.0) else {
+        return;
+    };
+    if let Ok(mut constraint) = constraints.get_mut(entity) {
+        constraint.target = cursor;
+    }
+}
+
+fn end_drag(
+    mut commands: Commands,
+    buttons: Res<ButtonInput<MouseButton>>,
+    mut state: ResMut<DragState>,
+) {
+    if !buttons.just_released(MouseButton::Left) {
+        return;
+    }
+    if let Some(entity) = state.entity.take() {
+        if let Some(mut entity_commands) = commands.get_entity(entity) {
+            entity_commands.remove::<PointConstraint>();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::{
+        app::{App, Update},
+        input::{mouse::MouseButton, ButtonInput},
+        math::Vec2,
+    };
+
+    use super::{start_drag, CursorWorldPos, DragState};
+    use crate::{ParticleBundle, PointConstraint};
+
+    #[test]
+    fn pinned_body_is_not_picked_up() {
+        // arrange
+        let mut app = App::new();
+        let mut buttons = ButtonInput::<MouseButton>::default();
+        buttons.press(MouseButton::Left);
+        app.insert_resource(buttons)
+            .insert_resource(CursorWorldPos(Some(Vec2::ZERO)))
+            .insert_resource(DragState {
+                compliance: 0.001,
+                entity: None,
+            })
+            .add_systems(Update, start_drag);
+        let pin = Vec2::new(0., 0.2);
+        let body = app
+            .world
+            .spawn((ParticleBundle::default(), PointConstraint::new(pin, 0.)))
+            .id();
+
+        // act
+        app.update();
+
+        // assert
+        let constraint = app.world.get::<PointConstraint>(body).unwrap();
+        assert_eq!(constraint.target, pin);
+        assert_eq!(constraint.compliance, 0.);
+        assert!(app.world.resource::<DragState>().entity.is_none());
+    }
+}
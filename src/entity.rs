@@ -1,8 +1,8 @@
 use bevy::{ecs::bundle::Bundle, math::Vec2};
 
 use crate::{
-    components::Aabb, BoxCollider, CircleCollider, Mass, Pos, PreSolveVel, PrevPos, Restitution,
-    Vel, SUB_DT,
+    components::Aabb, BoxCollider, CircleCollider, Inertia, InvMass, Mass, Pos, PreSolveVel,
+    PrevPos, Restitution, RigidBody, Vel,
 };
 
 #[derive(Bundle, Default)]
@@ -10,6 +10,9 @@ pub struct DynamicBoxBundle {
     pub pos: Pos,
     pub prev_pos: PrevPos,
     pub mass: Mass,
+    pub inv_mass: InvMass,
+    pub inertia: Inertia,
+    pub rigid_body: RigidBody,
     pub collider: BoxCollider,
     pub vel: Vel,
     pub presolve_vel: PreSolveVel,
@@ -21,7 +24,7 @@ impl DynamicBoxBundle {
     pub fn new_with_pos_and_vel(pos: Vec2, vel: Vec2) -> Self {
         Self {
             pos: Pos(pos),
-            prev_pos: PrevPos(pos - vel * SUB_DT),
+            prev_pos: PrevPos(pos),
             vel: Vel(vel),
             ..Default::default()
         }
@@ -33,6 +36,9 @@ pub struct ParticleBundle {
     pub pos: Pos,
     pub prev_pos: PrevPos,
     pub mass: Mass,
+    pub inv_mass: InvMass,
+    pub inertia: Inertia,
+    pub rigid_body: RigidBody,
     pub collider: CircleCollider,
     pub vel: Vel,
     pub presolve_vel: PreSolveVel,
@@ -44,23 +50,47 @@ impl ParticleBundle {
     pub fn new_with_pos_and_vel(pos: Vec2, vel: Vec2) -> Self {
         Self {
             pos: Pos(pos),
-            prev_pos: PrevPos(pos - vel * SUB_DT),
+            prev_pos: PrevPos(pos),
             vel: Vel(vel),
             ..Default::default()
         }
     }
 }
 
-#[derive(Bundle, Default)]
+#[derive(Bundle)]
 pub struct StaticCircleBundle {
     pub pos: Pos,
+    pub rigid_body: RigidBody,
     pub collider: CircleCollider,
     pub restitution: Restitution,
 }
 
-#[derive(Bundle, Default)]
+impl Default for StaticCircleBundle {
+    fn default() -> Self {
+        Self {
+            pos: Pos::default(),
+            rigid_body: RigidBody::Static,
+            collider: CircleCollider::default(),
+            restitution: Restitution::default(),
+        }
+    }
+}
+
+#[derive(Bundle)]
 pub struct StaticBoxBundle {
     pub pos: Pos,
+    pub rigid_body: RigidBody,
     pub collider: BoxCollider,
     pub restitution: Restitution,
 }
+
+impl Default for StaticBoxBundle {
+    fn default() -> Self {
+        Self {
+            pos: Pos::default(),
+            rigid_body: RigidBody::Static,
+            collider: BoxCollider::default(),
+            restitution: Restitution::default(),
+        }
+    }
+}
@@ -0,0 +1,195 @@
+use bevy::{
+    ecs::{system::Command, world::World},
+    math::Vec2,
+};
+
+use crate::{
+    raycast::{ray_box, ray_circle},
+    Aabb, BoxCollider, CircleCollider, Falloff, InvMass, Pos, RigidBody, Vel,
+};
+
+/// Command pushing every dynamic body whose `Aabb` overlaps the blast radius directly away from
+/// the centre, as in `commands.add(Explosion { .. })`
+#[derive(Clone, Copy, Debug)]
+pub struct Explosion {
+    pub center: Vec2,
+    pub radius: f32,
+    /// Impulse in newton seconds given to a body at the centre
+    pub impulse: f32,
+    /// How the impulse weakens with distance from the centre, capped at the full impulse for
+    /// `InverseSquare` within a metre
+    pub falloff: Falloff,
+    /// Whether static bodies in the way shelter bodies behind them. Statics containing or
+    /// touching the centre are ignored.
+    pub occluded_by_statics: bool,
+}
+
+impl Default for Explosion {
+    fn default() -> Self {
+        Self {
+            center: Vec2::ZERO,
+            radius: 1.,
+            impulse: 1.,
+            falloff: Falloff::Linear,
+            occluded_by_statics: false,
+        }
+    }
+}
+
+/// Static collider shapes, for occlusion tests
+enum StaticShape {
+    Circle { centre: Vec2, radius: f32 },
+    Box { min: Vec2, max: Vec2 },
+}
+
+impl StaticShape {
+    fn contains(&self, point: Vec2) -> bool {
+        match *self {
+            StaticShape::Circle { centre, radius } => {
+                centre.distance_squared(point) <= radius * radius
+            }
+            StaticShape::Box { min, max } => point.cmpge(min).all() && point.cmple(max).all(),
+        }
+    }
+
+    fn blocks(&self, origin: Vec2, direction: Vec2, distance: f32) -> bool {
+        let hit = match *self {
+            StaticShape::Circle { centre, radius } => ray_circle(origin, direction, centre, radius),
+            StaticShape::Box { min, max } => ray_box(origin, direction, min, max),
+        };
+        hit.is_some_and(|hit| hit < distance)
+    }
+}
+
+impl Explosion {
+    fn overlaps(&self, aabb: &Aabb) -> bool {
+        let closest = self.center.clamp(aabb.min, aabb.max);
+        closest.distance_squared(self.center) <= self.radius * self.radius
+    }
+
+    fn statics(world: &mut World) -> Vec<StaticShape> {
+        let mut query = world.query::<(
+            &Pos,
+            &RigidBody,
+            Option<&CircleCollider>,
+            Option<&BoxCollider>,
+        )>();
+        query
+            .iter(world)
+            .filter(|(_, rigid_body, ..)| rigid_body.is_static())
+            .filter_map(|(pos, _, circle, r#box)| match (circle, r#box) {
+                (Some(circle), _) => Some(StaticShape::Circle {
+                    centre: pos.0,
+                    radius: circle.radius,
+                }),
+                (None, Some(r#box)) => Some(StaticShape::Box {
+                    min: pos.0 - r#box.size / 2.,
+                    max: pos.0 + r#box.size / 2.,
+                }),
+                (None, None) => None,
+            })
+            .collect()
+    }
+}
+
+impl Command for Explosion {
+    fn apply(self, world: &mut World) {
+        let mut statics = if self.occluded_by_statics {
+            Self::statics(world)
+        } else {
+            Vec::new()
+        };
+        // every ray would start inside these, such as the floor for a blast on the ground, so
+        // they cannot shelter anything
+        statics.retain(|shape| !shape.contains(self.center));
+        let mut query = world.query::<(&Pos, &Aabb, &mut Vel, &InvMass, &RigidBody)>();
+        for (pos, aabb, mut vel, inv_mass, rigid_body) in query.iter_mut(world) {
+            if !rigid_body.is_dynamic() || !self.overlaps(aabb) {
+                continue;
+            }
+            let offset = pos.0 - self.center;
+            let distance = offset.length();
+            // a body right at the centre has no outward direction, so send it upwards
+            let direction = if distance > f32::EPSILON {
+                offset / distance
+            } else {
+                Vec2::Y
+            };
+            if statics
+                .iter()
+                .any(|shape| shape.blocks(self.center, direction, distance))
+            {
+                continue;
+            }
+            let factor = self.falloff.factor(distance, self.radius).clamp(0., 1.);
+            vel.0 += direction * self.impulse * factor * inv_mass.0;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::{
+        ecs::{entity::Entity, system::Command, world::World},
+        math::Vec2,
+    };
+
+    use super::Explosion;
+    use crate::{Aabb, BoxCollider, Falloff, InvMass, ParticleBundle, Pos, StaticBoxBundle, Vel};
+
+    fn spawn_body(world: &mut World, pos: Vec2) -> Entity {
+        world
+            .spawn(ParticleBundle {
+                inv_mass: InvMass(0.5),
+                aabb: Aabb {
+                    min: pos - Vec2::splat(0.5),
+                    max: pos + Vec2::splat(0.5),
+                },
+                ..ParticleBundle::new_with_pos_and_vel(pos, Vec2::ZERO)
+            })
+            .id()
+    }
+
+    #[test]
+    fn explosion_pushes_bodies_in_range_unless_sheltered() {
+        // arrange
+        let mut world = World::new();
+        let near = spawn_body(&mut world, Vec2::new(1., 0.));
+        let edge = spawn_body(&mut world, Vec2::new(0., -4.2));
+        let far = spawn_body(&mut world, Vec2::new(-10., 0.));
+        let sheltered = spawn_body(&mut world, Vec2::new(0., 3.));
+        // the blast is on the surface of the floor, which must not shelter everything
+        world.spawn(StaticBoxBundle {
+            pos: Pos(Vec2::new(0., -0.5)),
+            collider: BoxCollider {
+                size: Vec2::new(4., 1.),
+            },
+            ..Default::default()
+        });
+        world.spawn(StaticBoxBundle {
+            pos: Pos(Vec2::new(0., 2.)),
+            collider: BoxCollider {
+                size: Vec2::new(2., 0.5),
+            },
+            ..Default::default()
+        });
+
+        // act
+        Explosion {
+            center: Vec2::ZERO,
+            radius: 4.,
+            impulse: 8.,
+            falloff: Falloff::Linear,
+            occluded_by_statics: true,
+        }
+        .apply(&mut world);
+
+        // assert
+        let vel = |entity| world.get::<Vel>(entity).unwrap().get();
+        assert_eq!(vel(near), Vec2::new(3., 0.));
+        // only the Aabb overlaps, so the linear falloff gives nothing
+        assert_eq!(vel(edge), Vec2::ZERO);
+        assert_eq!(vel(far), Vec2::ZERO);
+        assert_eq!(vel(sheltered), Vec2::ZERO);
+    }
+}
@@ -0,0 +1,227 @@
+use std::f32::consts::PI;
+
+use bevy::{
+    ecs::{
+        bundle::Bundle,
+        component::Component,
+        query::With,
+        system::{Query, Res, Resource},
+    },
+    math::Vec2,
+};
+
+use crate::{
+    grid::NeighbourGrid, CircleCollider, Inertia, InvMass, Mass, Pos, PreSolveVel, PrevPos,
+    Restitution, RigidBody, Vel,
+};
+
+/// Marks a particle as part of a position based fluid (Macklin & Müller 2013). Fluid particles
+/// interact with each other through a density constraint rather than contacts, and collide with
+/// static bodies as usual.
+#[derive(Component, Debug, Default)]
+pub struct FluidParticle;
+
+/// Fluid particle without an `Aabb`, keeping it out of the broad phase. Fluid particles are all
+/// treated as having equal mass.
+#[derive(Bundle, Default)]
+pub struct FluidParticleBundle {
+    pub pos: Pos,
+    pub prev_pos: PrevPos,
+    pub mass: Mass,
+    pub inv_mass: InvMass,
+    pub inertia: Inertia,
+    pub rigid_body: RigidBody,
+    pub collider: CircleCollider,
+    pub vel: Vel,
+    pub presolve_vel: PreSolveVel,
+    pub restitution: Restitution,
+    pub fluid: FluidParticle,
+}
+
+impl FluidParticleBundle {
+    pub fn new_with_pos_and_vel(pos: Vec2, vel: Vec2) -> Self {
+        Self {
+            pos: Pos(pos),
+            prev_pos: PrevPos(pos),
+            vel: Vel(vel),
+            ..Default::default()
+        }
+    }
+}
+
+/// Parameters shared by all fluid particles
+#[derive(Debug, Resource)]
+pub struct FluidConfig {
+    /// Smoothing kernel radius, around two and a half particle spacings works well
+    pub kernel_radius: f32,
+    /// Density the fluid is held at, in particles per unit kernel-weighted area
+    pub rest_density: f32,
+    /// Softens the density constraint where particles have few neighbours
+    pub relaxation: f32,
+    /// XSPH viscosity, from 0 for an inviscid fluid to 1 for particles moving as one
+    pub viscosity: f32,
+    /// Pull of surface particles towards their neighbours each substep
+    pub surface_tension: f32,
+}
+
+impl FluidConfig {
+    /// Configuration for a fluid at rest with particles `spacing` apart on a square lattice
+    pub fn from_particle_spacing(spacing: f32) -> Self {
+        let kernel_radius = 2.5 * spacing;
+        let reach = (kernel_radius / spacing).ceil() as i32;
+        let rest_density = (-reach..=reach)
+            .flat_map(|y| (-reach..=reach).map(move |x| Vec2::new(x as f32, y as f32) * spacing))
+            .map(|offset| poly6(offset.length_squared(), kernel_radius))
+            .sum();
+        Self {
+            kernel_radius,
+            rest_density,
+            relaxation: 1.,
+            viscosity: 0.05,
+            surface_tension: 0.001,
+        }
+    }
+}
+
+impl Default for FluidConfig {
+    fn default() -> Self {
+        Self::from_particle_spacing(0.1)
+    }
+}
+
+/// 2D poly6 smoothing kernel, taking the squared distance
+fn poly6(distance_squared: f32, kernel_radius: f32) -> f32 {
+    let h_squared = kernel_radius * kernel_radius;
+    if distance_squared >= h_squared {
+        return 0.;
+    }
+    4. / (PI * h_squared.powi(4)) * (h_squared - distance_squared).powi(3)
+}
+
+/// Gradient of the 2D spiky smoothing kernel
+fn spiky_gradient(offset: Vec2, kernel_radius: f32) -> Vec2 {
+    let distance = offset.length();
+    if distance >= kernel_radius || distance <= f32::EPSILON {
+        return Vec2::ZERO;
+    }
+    offset / distance * (-30. / (PI * kernel_radius.powi(5)) * (kernel_radius - distance).powi(2))
+}
+
+/// density constraint, followed by cohesion between surface particles
+pub(crate) fn solve_fluid_density(
+    mut query: Query<(&mut Pos, &RigidBody), With<FluidParticle>>,
+    config: Res<FluidConfig>,
+) {
+    let positions: Vec<Vec2> = query.iter().map(|(pos, _)| pos.0).collect();
+    if positions.is_empty() {
+        return;
+    }
+    let h = config.kernel_radius;
+    let grid = NeighbourGrid::new(&positions, h);
+    let neighbours: Vec<Vec<usize>> = (0..positions.len())
+        .map(|index| grid.neighbours(&positions, index, h))
+        .collect();
+
+    let lambdas: Vec<f32> = positions
+        .iter()
+        .zip(&neighbours)
+        .map(|(&position, neighbours)| {
+            let density = poly6(0., h)
+                + neighbours
+                    .iter()
+                    .map(|&j| poly6(position.distance_squared(positions[j]), h))
+                    .sum::<f32>();
+            // only resist compression, so that the free surface does not clump
+            let c = (density / config.rest_density - 1.).max(0.);
+            if c <= 0. {
+                return 0.;
+            }
+            let mut gradient_sum = Vec2::ZERO;
+            let mut gradient_length_squared_sum = 0.;
+            for &j in neighbours {
+                let gradient = spiky_gradient(position - positions[j], h) / config.rest_density;
+                gradient_sum += gradient;
+                gradient_length_squared_sum += gradient.length_squared();
+            }
+            gradient_length_squared_sum += gradient_sum.length_squared();
+            -c / (gradient_length_squared_sum + config.relaxation)
+        })
+        .collect();
+
+    let deltas = positions.iter().enumerate().map(|(i, &position)| {
+        let mut density_correction = Vec2::ZERO;
+        let mut weighted_offset = Vec2::ZERO;
+        let mut weight_sum = 0.;
+        for &j in &neighbours[i] {
+            let offset = position - positions[j];
+            density_correction += (lambdas[i] + lambdas[j]) * spiky_gradient(offset, h);
+            let weight = poly6(offset.length_squared(), h);
+            weighted_offset -= weight * offset;
+            weight_sum += weight;
+        }
+        let cohesion = if weight_sum > 0. {
+            config.surface_tension * weighted_offset / weight_sum
+        } else {
+            Vec2::ZERO
+        };
+        density_correction / config.rest_density + cohesion
+    });
+
+    for ((mut pos, rigid_body), delta) in query.iter_mut().zip(deltas) {
+        if rigid_body.is_dynamic() {
+            pos.0 += delta;
+        }
+    }
+}
+
+/// XSPH viscosity, blending each particle velocity towards that of its neighbours
+pub(crate) fn apply_fluid_viscosity(
+    mut query: Query<(&Pos, &mut Vel, &RigidBody), With<FluidParticle>>,
+    config: Res<FluidConfig>,
+) {
+    if config.viscosity <= 0. {
+        return;
+    }
+    let (positions, velocities): (Vec<Vec2>, Vec<Vec2>) =
+        query.iter().map(|(pos, vel, _)| (pos.0, vel.0)).unzip();
+    if positions.is_empty() {
+        return;
+    }
+    let h = config.kernel_radius;
+    let grid = NeighbourGrid::new(&positions, h);
+
+    let deltas = (0..positions.len()).map(|i| {
+        let mut weighted_velocity = Vec2::ZERO;
+        let mut weight_sum = poly6(0., h);
+        for j in grid.neighbours(&positions, i, h) {
+            let weight = poly6(positions[i].distance_squared(positions[j]), h);
+            weighted_velocity += weight * (velocities[j] - velocities[i]);
+            weight_sum += weight;
+        }
+        config.viscosity * weighted_velocity / weight_sum
+    });
+
+    for ((_, mut vel, rigid_body), delta) in query.iter_mut().zip(deltas) {
+        if rigid_body.is_dynamic() {
+            vel.0 += delta;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::FluidConfig;
+
+    #[test]
+    fn rest_density_is_positive_and_scales_with_spacing() {
+        // arrange
+        let coarse = FluidConfig::from_particle_spacing(0.2);
+
+        // act
+        let fine = FluidConfig::from_particle_spacing(0.1);
+
+        // assert
+        assert!(coarse.rest_density > 0.);
+        assert!((fine.rest_density / coarse.rest_density - 4.).abs() < 1e-3);
+    }
+}
@@ -0,0 +1,212 @@
+use bevy::{
+    ecs::{
+        component::Component,
+        system::{Query, Res},
+    },
+    math::Vec2,
+};
+
+use crate::{
+    resources::{Contacts, StaticContacts},
+    InvMass, PhysicsTime, Pos, PrevPos, RigidBody, Vel,
+};
+
+/// Friction and rolling resistance for particle contacts, so that sand and gravel heap up at an
+/// angle of repose instead of flowing flat. Contacts between two particles need the component on
+/// both, contacts with static bodies use the particle's own values. Particles do not spin, so
+/// rolling resistance is modelled as damping of the sliding velocity at each contact, standing
+/// in for the torque which would stop a real grain rolling down the slope. Sliding friction only
+/// acts against static bodies, gripping the base of the pile: between particles it would hold
+/// grains which should roll off each other, building towers which slowly creep and collapse.
+#[derive(Component, Clone, Copy, Debug)]
+pub struct GranularMaterial {
+    pub static_friction: f32,
+    pub dynamic_friction: f32,
+    pub rolling_resistance: f32,
+}
+
+impl Default for GranularMaterial {
+    fn default() -> Self {
+        Self {
+            static_friction: 0.6,
+            dynamic_friction: 0.4,
+            rolling_resistance: 0.1,
+        }
+    }
+}
+
+impl GranularMaterial {
+    fn combine(self, other: Self) -> Self {
+        Self {
+            static_friction: (self.static_friction + other.static_friction) / 2.,
+            dynamic_friction: (self.dynamic_friction + other.dynamic_friction) / 2.,
+            rolling_resistance: (self.rolling_resistance + other.rolling_resistance) / 2.,
+        }
+    }
+
+    /// Position correction cancelling some or all of the tangential part of `displacement`,
+    /// given a contact which was just pushed apart by `penetration`
+    fn friction_correction(self, displacement: Vec2, normal: Vec2, penetration: f32) -> Vec2 {
+        let tangential = displacement - displacement.dot(normal) * normal;
+        let length = tangential.length();
+        if length < self.static_friction * penetration {
+            tangential
+        } else if length > f32::EPSILON {
+            tangential * (self.dynamic_friction * penetration / length).min(1.)
+        } else {
+            Vec2::ZERO
+        }
+    }
+
+    /// Velocity change slowing the tangential part of `relative_vel`
+    fn rolling_correction(
+        self,
+        relative_vel: Vec2,
+        normal: Vec2,
+        penetration: f32,
+        sub_dt: f32,
+    ) -> Vec2 {
+        let tangential = relative_vel - relative_vel.dot(normal) * normal;
+        let length = tangential.length();
+        if length <= f32::EPSILON {
+            return Vec2::ZERO;
+        }
+        let normal_speed = penetration / sub_dt;
+        tangential * (self.rolling_resistance * normal_speed / length).min(1.)
+    }
+}
+
+pub(crate) fn solve_granular_friction(
+    mut bodies: Query<(&mut Pos, &PrevPos, &GranularMaterial)>,
+    static_contacts: Res<StaticContacts>,
+) {
+    for &(entity, _, normal, penetration) in &static_contacts.0 {
+        let Ok((mut pos, prev_pos, material)) = bodies.get_mut(entity) else {
+            continue;
+        };
+        let displacement = pos.0 - prev_pos.0;
+        pos.0 -= material.friction_correction(displacement, normal, penetration);
+    }
+}
+
+pub(crate) fn apply_rolling_resistance(
+    mut bodies: Query<(&mut Vel, &InvMass, &RigidBody, Option<&GranularMaterial>)>,
+    contacts: Res<Contacts>,
+    static_contacts: Res<StaticContacts>,
+    time: Res<PhysicsTime>,
+) {
+    let sub_dt = time.substep_delta_seconds();
+    for &(entity_a, entity_b, normal, penetration) in &contacts.0 {
+        let Ok([body_a, body_b]) = bodies.get_many_mut([entity_a, entity_b]) else {
+            continue;
+        };
+        let (
+            (mut vel_a, inv_mass_a, rigid_body_a, Some(material_a)),
+            (mut vel_b, inv_mass_b, rigid_body_b, Some(material_b)),
+        ) = (body_a, body_b)
+        else {
+            continue;
+        };
+        let w_a = rigid_body_a.inverse_mass(inv_mass_a);
+        let w_b = rigid_body_b.inverse_mass(inv_mass_b);
+        let w_sum = w_a + w_b;
+        if w_sum <= 0. {
+            continue;
+        }
+        let correction = material_a.combine(*material_b).rolling_correction(
+            vel_a.0 - vel_b.0,
+            normal,
+            penetration,
+            sub_dt,
+        );
+        vel_a.0 -= correction * (w_a / w_sum);
+        vel_b.0 += correction * (w_b / w_sum);
+    }
+
+    for &(entity, _, normal, penetration) in &static_contacts.0 {
+        let Ok((mut vel, _, _, Some(material))) = bodies.get_mut(entity) else {
+            continue;
+        };
+        let correction = material.rolling_correction(vel.0, normal, penetration, sub_dt);
+        vel.0 -= correction;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::{app::App, math::Vec2};
+
+    use super::GranularMaterial;
+    use crate::{
+        BoxCollider, CircleCollider, ParticleBundle, PhysicsSchedule, PhysicsWorld, Pos,
+        Restitution, StaticBoxBundle, Vel, XPBDPlugin,
+    };
+
+    const RADIUS: f32 = 0.05;
+
+    /// pile height above the floor, half width of the base and fastest particle speed
+    fn pile_profile(app: &mut App) -> (f32, f32, f32) {
+        let mut query = app.world.query::<(&Pos, &Vel)>();
+        let mut height = 0_f32;
+        let mut max_speed = 0_f32;
+        let mut offsets = Vec::new();
+        for (pos, vel) in query.iter(&app.world) {
+            height = height.max(pos.0.y + RADIUS);
+            max_speed = max_speed.max(vel.0.length());
+            offsets.push(pos.0.x.abs() + RADIUS);
+        }
+        // ignore the odd grain which bounced clear of the pile
+        offsets.sort_by(f32::total_cmp);
+        let half_width = offsets[offsets.len() * 9 / 10];
+        (height, half_width, max_speed)
+    }
+
+    #[test]
+    fn poured_pile_reaches_stable_slope() {
+        // arrange
+        let mut app = App::new();
+        app.add_plugins(XPBDPlugin::new(PhysicsSchedule));
+        app.world.spawn(StaticBoxBundle {
+            pos: Pos(Vec2::new(0., -0.5)),
+            collider: BoxCollider {
+                size: Vec2::new(20., 1.),
+            },
+            ..Default::default()
+        });
+
+        // act
+        for grain in 0..150 {
+            let jitter = ((grain * 7) % 5) as f32 * 0.004 - 0.008;
+            app.world.spawn((
+                ParticleBundle {
+                    collider: CircleCollider { radius: RADIUS },
+                    restitution: Restitution(0.),
+                    // fast enough to clear the spawn point before the next grain arrives
+                    ..ParticleBundle::new_with_pos_and_vel(
+                        Vec2::new(jitter, 1.5),
+                        Vec2::new(0., -3.),
+                    )
+                },
+                GranularMaterial::default(),
+            ));
+            for _ in 0..3 {
+                PhysicsWorld::step(&mut app.world);
+            }
+        }
+        for _ in 0..600 {
+            PhysicsWorld::step(&mut app.world);
+        }
+        let (height, half_width, max_speed) = pile_profile(&mut app);
+        for _ in 0..300 {
+            PhysicsWorld::step(&mut app.world);
+        }
+        let (later_height, later_half_width, _) = pile_profile(&mut app);
+
+        // assert
+        let slope = height / half_width;
+        assert!(slope > 0.3, "pile slope {slope} is too shallow");
+        assert!(max_speed < 0.1, "pile still moving at {max_speed} m/s");
+        assert!((later_height - height).abs() < RADIUS);
+        assert!((later_half_width - half_width).abs() < 2. * RADIUS);
+    }
+}
@@ -0,0 +1,520 @@
+use std::ops::Range;
+
+use bevy::{
+    ecs::{
+        component::Component,
+        entity::Entity,
+        query::With,
+        system::{Query, Res, Resource},
+    },
+    math::Vec2,
+};
+
+use crate::{GravityScale, Mass, PhysicsTime, Pos, RigidBody, Vel};
+
+/// How a `GravitySource`'s pull weakens with distance
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Falloff {
+    /// The full strength everywhere within the radius
+    Constant,
+    /// Fading linearly from the full strength at the centre to nothing at the radius
+    Linear,
+    /// Newtonian gravity, `strength` being the gravitational parameter G * M
+    #[default]
+    InverseSquare,
+}
+
+impl Falloff {
+    /// Fraction of the full strength felt at `distance` from the centre, within `radius`
+    pub fn factor(self, distance: f32, radius: f32) -> f32 {
+        match self {
+            Falloff::Constant => 1.,
+            Falloff::Linear => 1. - distance / radius,
+            Falloff::InverseSquare => 1. / (distance * distance),
+        }
+    }
+}
+
+/// Attractor pulling dynamic bodies within `radius` towards its `Pos`, with an acceleration of
+/// `strength` shaped by the falloff. Bodies keep orbiting stably as the integration is
+/// symplectic.
+#[derive(Component, Clone, Copy, Debug)]
+pub struct GravitySource {
+    pub strength: f32,
+    pub falloff: Falloff,
+    /// Distance beyond which the source has no effect, which can be infinite
+    pub radius: f32,
+}
+
+impl GravitySource {
+    /// Acceleration felt at `offset` from the source
+    pub fn acceleration(&self, offset: Vec2) -> Vec2 {
+        let distance_squared = offset.length_squared();
+        if distance_squared > self.radius * self.radius || distance_squared <= f32::EPSILON {
+            return Vec2::ZERO;
+        }
+        let distance = distance_squared.sqrt();
+        -offset / distance * self.strength * self.falloff.factor(distance, self.radius)
+    }
+}
+
+pub(crate) fn apply_gravity_sources(
+    sources: Query<(Entity, &Pos, &GravitySource)>,
+    mut bodies: Query<(Entity, &Pos, &mut Vel, &RigidBody, Option<&GravityScale>)>,
+    time: Res<PhysicsTime>,
+) {
+    let sources: Vec<(Entity, Vec2, GravitySource)> = sources
+        .iter()
+        .map(|(entity, pos, source)| (entity, pos.0, *source))
+        .collect();
+    if sources.is_empty() {
+        return;
+    }
+    let sub_dt = time.substep_delta_seconds();
+    for (entity, pos, mut vel, rigid_body, gravity_scale) in bodies.iter_mut() {
+        if !rigid_body.is_dynamic() {
+            continue;
+        }
+        let acceleration: Vec2 = sources
+            .iter()
+            .filter(|(source_entity, _, _)| *source_entity != entity)
+            .map(|(_, source_pos, source)| source.acceleration(pos.0 - *source_pos))
+            .sum();
+        vel.0 += sub_dt * acceleration * gravity_scale.map_or(1., |scale| scale.0);
+    }
+}
+
+/// Marks a body as attracting, and being attracted by, every other `MutualGravity` body in
+/// proportion to its `Mass`. Static and kinematic bodies attract without being moved; static
+/// bundles have no `Mass`, so add one alongside the marker.
+#[derive(Component, Debug, Default)]
+pub struct MutualGravity;
+
+/// Parameters for `MutualGravity`
+#[derive(Clone, Copy, Debug, Resource)]
+pub struct MutualGravitySettings {
+    pub gravitational_constant: f32,
+    /// Barnes-Hut opening angle: a quadtree cell whose size over distance is below this is
+    /// treated as a single mass. Zero gives the exact sum, around 0.5 is a good compromise.
+    pub opening_angle: f32,
+    /// Plummer softening length, keeping close encounters from producing huge accelerations
+    pub softening: f32,
+}
+
+impl Default for MutualGravitySettings {
+    fn default() -> Self {
+        Self {
+            gravitational_constant: 1.,
+            opening_angle: 0.5,
+            softening: 0.05,
+        }
+    }
+}
+
+impl MutualGravitySettings {
+    /// Acceleration towards `mass` at `offset`, softened
+    fn pull(&self, offset: Vec2, mass: f32) -> Vec2 {
+        let distance_squared = offset.length_squared() + self.softening * self.softening;
+        if distance_squared <= f32::EPSILON {
+            return Vec2::ZERO;
+        }
+        offset * (self.gravitational_constant * mass / (distance_squared * distance_squared.sqrt()))
+    }
+}
+
+/// Deepest quadtree level, so that coincident points end up sharing a leaf
+const MAX_DEPTH: u32 = 24;
+
+#[derive(Debug)]
+struct QuadNode {
+    centre: Vec2,
+    half_size: f32,
+    mass: f32,
+    centre_of_mass: Vec2,
+    /// range of `QuadTree::order` holding the points in this cell
+    points: Range<usize>,
+    children: Option<[usize; 4]>,
+}
+
+/// Quadtree of point masses with the total mass and centre of mass of each cell
+#[derive(Debug)]
+struct QuadTree {
+    nodes: Vec<QuadNode>,
+    order: Vec<usize>,
+}
+
+impl QuadTree {
+    fn new(points: &[Vec2], masses: &[f32]) -> Self {
+        let min = points.iter().copied().fold(Vec2::INFINITY, Vec2::min);
+        let max = points.iter().copied().fold(Vec2::NEG_INFINITY, Vec2::max);
+        let mut tree = Self {
+            nodes: Vec::new(),
+            order: (0..points.len()).collect(),
+        };
+        if !points.is_empty() {
+            let half_size = ((max - min).max_element() / 2.).max(f32::EPSILON);
+            tree.build(
+                points,
+                masses,
+                (min + max) / 2.,
+                half_size,
+                0..points.len(),
+                0,
+            );
+        }
+        tree
+    }
+
+    fn build(
+        &mut self,
+        points: &[Vec2],
+        masses: &[f32],
+        centre: Vec2,
+        half_size: f32,
+        range: Range<usize>,
+        depth: u32,
+    ) -> usize {
+        let (mass, weighted_sum) = self.order[range.clone()]
+            .iter()
+            .fold((0., Vec2::ZERO), |(mass, sum), &i| {
+                (mass + masses[i], sum + points[i] * masses[i])
+            });
+        let centre_of_mass = if mass > 0. {
+            weighted_sum / mass
+        } else {
+            centre
+        };
+        let index = self.nodes.len();
+        self.nodes.push(QuadNode {
+            centre,
+            half_size,
+            mass,
+            centre_of_mass,
+            points: range.clone(),
+            children: None,
+        });
+        if range.len() <= 1 || depth >= MAX_DEPTH {
+            return index;
+        }
+
+        let quadrant =
+            |point: Vec2| usize::from(point.x >= centre.x) + 2 * usize::from(point.y >= centre.y);
+        self.order[range.clone()].sort_unstable_by_key(|&i| quadrant(points[i]));
+        let mut children = [0; 4];
+        let mut start = range.start;
+        for (q, child) in children.iter_mut().enumerate() {
+            let end = start
+                + self.order[start..range.end]
+                    .iter()
+                    .take_while(|&&i| quadrant(points[i]) == q)
+                    .count();
+            let offset = Vec2::new(
+                if q % 2 == 1 { 0.5 } else { -0.5 },
+                if q / 2 == 1 { 0.5 } else { -0.5 },
+            );
+            *child = self.build(
+                points,
+                masses,
+                centre + offset * half_size,
+                half_size / 2.,
+                start..end,
+                depth + 1,
+            );
+            start = end;
+        }
+        self.nodes[index].children = Some(children);
+        index
+    }
+
+    fn acceleration(
+        &self,
+        points: &[Vec2],
+        masses: &[f32],
+        index: usize,
+        settings: &MutualGravitySettings,
+    ) -> Vec2 {
+        let point = points[index];
+        let mut acceleration = Vec2::ZERO;
+        let mut stack = if self.nodes.is_empty() {
+            vec![]
+        } else {
+            vec![0]
+        };
+        while let Some(node) = stack.pop() {
+            let node = &self.nodes[node];
+            if node.mass <= 0. {
+                continue;
+            }
+            let offset = node.centre_of_mass - point;
+            let contains_point = ((point - node.centre).abs().max_element()) <= node.half_size;
+            let size = 2. * node.half_size;
+            let far_enough = size * size
+                < settings.opening_angle * settings.opening_angle * offset.length_squared();
+            match node.children {
+                Some(children) if contains_point || !far_enough => stack.extend(children),
+                Some(_) => acceleration += settings.pull(offset, node.mass),
+                None => {
+                    for &other in &self.order[node.points.clone()] {
+                        if other != index {
+                            acceleration += settings.pull(points[other] - point, masses[other]);
+                        }
+                    }
+                }
+            }
+        }
+        acceleration
+    }
+}
+
+/// Accelerations of point masses under their mutual gravity, approximated with a Barnes-Hut
+/// quadtree in O(n log n)
+pub fn barnes_hut_accelerations(
+    points: &[Vec2],
+    masses: &[f32],
+    settings: &MutualGravitySettings,
+) -> Vec<Vec2> {
+    let tree = QuadTree::new(points, masses);
+    (0..points.len())
+        .map(|index| tree.acceleration(points, masses, index, settings))
+        .collect()
+}
+
+/// Accelerations of point masses under their mutual gravity, summing every pair in O(n²)
+pub fn exact_accelerations(
+    points: &[Vec2],
+    masses: &[f32],
+    settings: &MutualGravitySettings,
+) -> Vec<Vec2> {
+    points
+        .iter()
+        .enumerate()
+        .map(|(index, &point)| {
+            points
+                .iter()
+                .zip(masses)
+                .enumerate()
+                .filter(|&(other, _)| other != index)
+                .map(|(_, (&other_point, &mass))| settings.pull(other_point - point, mass))
+                .sum()
+        })
+        .collect()
+}
+
+#[allow(clippy::type_complexity)]
+pub(crate) fn apply_mutual_gravity(
+    mut bodies: Query<
+        (
+            &Pos,
+            &Mass,
+            Option<&mut Vel>,
+            &RigidBody,
+            Option<&GravityScale>,
+        ),
+        With<MutualGravity>,
+    >,
+    settings: Res<MutualGravitySettings>,
+    time: Res<PhysicsTime>,
+) {
+    // bodies of infinite mass would pull everything in at once
+    let (points, masses): (Vec<Vec2>, Vec<f32>) = bodies
+        .iter()
+        .map(|(pos, mass, ..)| (pos.0, if mass.0.is_finite() { mass.0 } else { 0. }))
+        .unzip();
+    if points.len() < 2 {
+        return;
+    }
+    let accelerations = barnes_hut_accelerations(&points, &masses, &settings);
+    let sub_dt = time.substep_delta_seconds();
+    for ((_, _, vel, rigid_body, gravity_scale), acceleration) in
+        bodies.iter_mut().zip(accelerations)
+    {
+        // static attractors have no velocity to update
+        if let Some(mut vel) = vel.filter(|_| rigid_body.is_dynamic()) {
+            vel.0 += sub_dt * acceleration * gravity_scale.map_or(1., |scale| scale.0);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::{app::App, ecs::entity::Entity, math::Vec2};
+
+    use rand::{rngs::StdRng, Rng, SeedableRng};
+
+    use super::{
+        barnes_hut_accelerations, exact_accelerations, Falloff, GravitySource, MutualGravity,
+        MutualGravitySettings,
+    };
+    use crate::{
+        tests::zero_gravity_app, Mass, ParticleBundle, PhysicsWorld, Pos, RigidBody,
+        StaticCircleBundle, Vel,
+    };
+
+    const GM: f32 = 50.;
+
+    /// specific orbital energy and distance from the source
+    fn orbital_energy(app: &App, body: Entity) -> (f32, f32) {
+        let r = app.world.get::<Pos>(body).unwrap().0.length();
+        let v = app.world.get::<Vel>(body).unwrap().get().length();
+        (v * v / 2. - GM / r, r)
+    }
+
+    #[test]
+    fn circular_orbit_conserves_energy() {
+        // arrange
+        let mut app = zero_gravity_app();
+        app.world.spawn((
+            Pos(Vec2::ZERO),
+            RigidBody::Static,
+            GravitySource {
+                strength: GM,
+                falloff: Falloff::InverseSquare,
+                radius: f32::INFINITY,
+            },
+        ));
+        let radius = 5.;
+        let speed = (GM / radius).sqrt();
+        let body = app
+            .world
+            .spawn(ParticleBundle::new_with_pos_and_vel(
+                Vec2::new(radius, 0.),
+                Vec2::new(0., speed),
+            ))
+            .id();
+        let (initial_energy, _) = orbital_energy(&app, body);
+
+        // act
+        // around five orbits
+        let mut max_drift = 0_f32;
+        let mut max_radius_error = 0_f32;
+        for _ in 0..3000 {
+            PhysicsWorld::step(&mut app.world);
+            let (energy, r) = orbital_energy(&app, body);
+            max_drift = max_drift.max(((energy - initial_energy) / initial_energy).abs());
+            max_radius_error = max_radius_error.max((r - radius).abs());
+        }
+
+        // assert
+        assert!(max_drift < 0.01, "orbital energy drifted by {max_drift}");
+        assert!(
+            max_radius_error < 0.05,
+            "orbit radius strayed by {max_radius_error}"
+        );
+    }
+
+    #[test]
+    fn falloffs_shape_acceleration() {
+        // arrange
+        let source = |falloff| GravitySource {
+            strength: 4.,
+            falloff,
+            radius: 4.,
+        };
+        let offset = Vec2::new(2., 0.);
+
+        // act
+        let constant = source(Falloff::Constant).acceleration(offset);
+        let linear = source(Falloff::Linear).acceleration(offset);
+        let inverse_square = source(Falloff::InverseSquare).acceleration(offset);
+        let outside = source(Falloff::Constant).acceleration(Vec2::new(5., 0.));
+
+        // assert
+        assert_eq!(constant, Vec2::new(-4., 0.));
+        assert_eq!(linear, Vec2::new(-2., 0.));
+        assert_eq!(inverse_square, Vec2::new(-1., 0.));
+        assert_eq!(outside, Vec2::ZERO);
+    }
+
+    fn random_cluster(count: usize) -> (Vec<Vec2>, Vec<f32>) {
+        let mut rng = StdRng::seed_from_u64(47);
+        (0..count)
+            .map(|_| {
+                let point = Vec2::new(rng.gen_range(-10.0..10.), rng.gen_range(-10.0..10.));
+                (point, rng.gen_range(0.5..2.))
+            })
+            .unzip()
+    }
+
+    #[test]
+    fn zero_opening_angle_matches_exact_sum() {
+        // arrange
+        let (points, masses) = random_cluster(200);
+        let settings = MutualGravitySettings {
+            opening_angle: 0.,
+            ..Default::default()
+        };
+
+        // act
+        let approximate = barnes_hut_accelerations(&points, &masses, &settings);
+        let exact = exact_accelerations(&points, &masses, &settings);
+
+        // assert
+        for (approximate, exact) in approximate.iter().zip(&exact) {
+            assert!((*approximate - *exact).length() <= 1e-4 * exact.length().max(1.));
+        }
+    }
+
+    #[test]
+    fn barnes_hut_error_is_small() {
+        // arrange
+        let (points, masses) = random_cluster(1000);
+        let settings = MutualGravitySettings::default();
+
+        // act
+        let approximate = barnes_hut_accelerations(&points, &masses, &settings);
+        let exact = exact_accelerations(&points, &masses, &settings);
+
+        // assert
+        let error: f32 = approximate
+            .iter()
+            .zip(&exact)
+            .map(|(approximate, exact)| (*approximate - *exact).length())
+            .sum();
+        let magnitude: f32 = exact.iter().map(|exact| exact.length()).sum();
+        assert!(
+            error / magnitude < 0.02,
+            "relative error {}",
+            error / magnitude
+        );
+    }
+
+    #[test]
+    fn static_body_with_mutual_gravity_attracts_without_moving() {
+        // arrange
+        let mut app = zero_gravity_app();
+        let attractor = app
+            .world
+            .spawn((StaticCircleBundle::default(), Mass(100.), MutualGravity))
+            .id();
+        let body = app
+            .world
+            .spawn((
+                ParticleBundle::new_with_pos_and_vel(Vec2::new(5., 0.), Vec2::ZERO),
+                MutualGravity,
+            ))
+            .id();
+
+        // act
+        PhysicsWorld::step(&mut app.world);
+
+        // assert
+        let vel = app.world.get::<Vel>(body).unwrap().get();
+        assert!(vel.x < 0., "body moving at {vel}");
+        assert!(vel.y.abs() < 1e-6);
+        assert_eq!(app.world.get::<Pos>(attractor).unwrap().0, Vec2::ZERO);
+    }
+
+    #[test]
+    fn coincident_points_do_not_recurse_forever() {
+        // arrange
+        let points = vec![Vec2::ONE; 8];
+        let masses = vec![1.; 8];
+
+        // act
+        let accelerations =
+            barnes_hut_accelerations(&points, &masses, &MutualGravitySettings::default());
+
+        // assert
+        assert!(accelerations.iter().all(|a| *a == Vec2::ZERO));
+    }
+}
@@ -0,0 +1,69 @@
+use std::collections::HashMap;
+
+use bevy::math::{IVec2, Vec2};
+
+/// Uniform grid bucketing points by cell, for finding neighbours no further apart than the cell
+/// size without testing every pair
+#[derive(Debug)]
+pub(crate) struct NeighbourGrid {
+    cell_size: f32,
+    cells: HashMap<IVec2, Vec<usize>>,
+}
+
+impl NeighbourGrid {
+    pub(crate) fn new(points: &[Vec2], cell_size: f32) -> Self {
+        let mut cells: HashMap<IVec2, Vec<usize>> = HashMap::new();
+        for (index, point) in points.iter().enumerate() {
+            cells
+                .entry(cell(*point, cell_size))
+                .or_default()
+                .push(index);
+        }
+        Self { cell_size, cells }
+    }
+
+    /// Indices of the other points within `radius` of `points[index]`. `radius` must not be
+    /// larger than the cell size.
+    pub(crate) fn neighbours(&self, points: &[Vec2], index: usize, radius: f32) -> Vec<usize> {
+        let point = points[index];
+        let centre = cell(point, self.cell_size);
+        (-1..=1)
+            .flat_map(|y| (-1..=1).map(move |x| centre + IVec2::new(x, y)))
+            .filter_map(|neighbour_cell| self.cells.get(&neighbour_cell))
+            .flatten()
+            .copied()
+            .filter(|&other| {
+                other != index && points[other].distance_squared(point) < radius * radius
+            })
+            .collect()
+    }
+}
+
+fn cell(point: Vec2, cell_size: f32) -> IVec2 {
+    (point / cell_size).floor().as_ivec2()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::NeighbourGrid;
+    use bevy::math::Vec2;
+
+    #[test]
+    fn neighbours_found_across_cell_boundaries() {
+        // arrange
+        let points = [
+            Vec2::new(0.95, 0.5),
+            Vec2::new(1.05, 0.5),
+            Vec2::new(0.5, -0.3),
+            Vec2::new(3., 3.),
+        ];
+        let grid = NeighbourGrid::new(&points, 1.);
+
+        // act
+        let mut result = grid.neighbours(&points, 0, 1.);
+        result.sort_unstable();
+
+        // assert
+        assert_eq!(result, vec![1, 2]);
+    }
+}
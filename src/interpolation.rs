@@ -0,0 +1,76 @@
+use bevy::{
+    ecs::{
+        entity::Entity,
+        query::Without,
+        system::{Commands, Query, Res, Resource, SystemParam},
+    },
+    math::Vec2,
+    time::{Fixed, Time},
+};
+
+use crate::{PhysicsTime, Pos, PrevStepPos, Vel};
+
+/// How rendered positions follow `Pos` when the render rate differs from the physics timestep.
+/// Bodies do not rotate, so only translation is affected.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Resource)]
+pub enum Interpolation {
+    /// Show the position reached by the latest timestep, which can stutter
+    #[default]
+    None,
+    /// Blend between the previous and latest timestep, a fraction of a timestep behind
+    Interpolate,
+    /// Project forward from the latest timestep along the velocity, which can overshoot
+    /// contacts
+    Extrapolate,
+}
+
+/// Where to draw bodies between timesteps, shared by transform and mesh syncing
+#[derive(SystemParam)]
+pub(crate) struct RenderBlend<'w> {
+    interpolation: Res<'w, Interpolation>,
+    fixed_time: Res<'w, Time<Fixed>>,
+    physics_time: Res<'w, PhysicsTime>,
+}
+
+impl RenderBlend<'_> {
+    pub(crate) fn position(
+        &self,
+        pos: &Pos,
+        prev_step_pos: Option<&PrevStepPos>,
+        vel: Option<&Vel>,
+    ) -> Vec2 {
+        // while paused the latest timestep stays on screen however far the accumulator has run
+        if self.physics_time.is_paused() {
+            return pos.0;
+        }
+        match (*self.interpolation, prev_step_pos, vel) {
+            (Interpolation::Interpolate, Some(prev_step_pos), _) => prev_step_pos
+                .0
+                .lerp(pos.0, self.fixed_time.overstep_fraction()),
+            (Interpolation::Extrapolate, _, Some(vel)) => {
+                let overstep = self.fixed_time.overstep().as_secs_f32();
+                pos.0 + vel.0 * overstep * self.physics_time.time_scale
+            }
+            _ => pos.0,
+        }
+    }
+}
+
+pub(crate) fn insert_prev_step_pos(
+    mut commands: Commands,
+    query: Query<(Entity, &Pos), Without<PrevStepPos>>,
+    interpolation: Res<Interpolation>,
+) {
+    if *interpolation == Interpolation::None {
+        return;
+    }
+    for (entity, pos) in query.iter() {
+        commands.entity(entity).insert(PrevStepPos(pos.0));
+    }
+}
+
+pub(crate) fn record_prev_step_pos(mut query: Query<(&mut PrevStepPos, &Pos)>) {
+    for (mut prev_step_pos, pos) in query.iter_mut() {
+        prev_step_pos.0 = pos.0;
+    }
+}
@@ -1,110 +1,294 @@
+mod buoyancy;
+mod cloth;
+mod commands;
 mod components;
+mod constraints;
 mod contact;
+mod drag;
 mod entity;
+mod explosion;
+mod fluid;
+mod granular;
+mod gravity;
+mod grid;
+mod interpolation;
+mod raycast;
 mod resources;
+mod rope;
+mod soft_body;
 
 use bevy::{
-    app::{App, FixedUpdate, Plugin, Update},
+    app::{App, FixedUpdate, Plugin, PreUpdate, Update},
     ecs::{
+        change_detection::DetectChangesMut,
         entity::Entity,
-        query::{With, Without},
-        schedule::{IntoSystemConfigs, Schedule, ScheduleLabel, SystemSet},
+        query::{Added, Changed, Or, Without},
+        schedule::{
+            common_conditions::resource_changed, InternedScheduleLabel, IntoSystemConfigs,
+            IntoSystemSetConfigs, ScheduleLabel, SystemSet,
+        },
         system::{Query, Res, ResMut},
         world::World,
     },
-    log::debug,
+    log::{debug, error},
     math::Vec2,
     time::{Fixed, Time},
 };
 
+use buoyancy::apply_buoyancy;
+pub use buoyancy::FluidVolume;
+pub use cloth::ClothBuilder;
+pub use commands::{BodyCommandsExt, SetLinearVelocity, Teleport};
 pub use components::{
-    Aabb, BoxCollider, CircleCollider, Mass, Pos, PreSolveVel, PrevPos, Restitution, Vel,
+    Aabb, BoxCollider, CircleCollider, Density, ExternalForce, ExternalImpulse, GravityOverride,
+    GravityScale, Inertia, InvMass, KinematicTarget, LinearDamping, Mass, MassError, Pos,
+    PreSolveVel, PrevPos, PrevStepPos, QuadraticDrag, Restitution, RigidBody, Vel,
+};
+use constraints::{
+    solve_area_constraints, solve_distance_constraints, solve_point_constraints,
+    solve_shape_matching_constraints, tear_constraints,
+};
+pub use constraints::{
+    AreaConstraint, ConstraintTorn, DistanceConstraint, PointConstraint, ShapeMatchingConstraint,
+    Tearable,
 };
 pub use contact::Contact;
+pub use drag::{CursorWorldPos, DragPlugin};
 pub use entity::{DynamicBoxBundle, ParticleBundle, StaticBoxBundle, StaticCircleBundle};
-pub use resources::Gravity;
-use resources::{CollisionPairs, Contacts, StaticContacts};
+pub use explosion::Explosion;
+use fluid::{apply_fluid_viscosity, solve_fluid_density};
+pub use fluid::{FluidConfig, FluidParticle, FluidParticleBundle};
+pub use granular::GranularMaterial;
+use granular::{apply_rolling_resistance, solve_granular_friction};
+use gravity::{apply_gravity_sources, apply_mutual_gravity};
+pub use gravity::{
+    barnes_hut_accelerations, exact_accelerations, Falloff, GravitySource, MutualGravity,
+    MutualGravitySettings,
+};
+pub use interpolation::Interpolation;
+use interpolation::{insert_prev_step_pos, record_prev_step_pos, RenderBlend};
+use resources::CollisionPairs;
+pub use resources::{
+    Contacts, Gravity, PhysicsConfig, PhysicsConfigError, PhysicsTime, StaticContacts,
+};
+pub use rope::{Rope, RopeBuilder};
+use soft_body::{remove_torn_triangles, sync_deformable_meshes};
+pub use soft_body::{DeformableMesh, SoftBody, SoftBodyBuilder};
 
-pub const DELTA_TIME: f32 = 1.0 / 60.0; // 60 fps
-pub const NUM_SUBSTEPS: u32 = 10;
-pub const SUB_DT: f32 = DELTA_TIME / NUM_SUBSTEPS as f32;
+/// All physics systems which run in `PhysicsSchedule`, once per timestep. The set is skipped
+/// while `PhysicsTime` is paused.
+#[derive(Debug, Hash, PartialEq, Eq, Clone, SystemSet)]
+pub struct PhysicsSet;
 
-/// Safety margin bigger then DELTA_TIME added to AABBs to account for sudden accelerations
-const COLLISION_PAIR_VEL_MARGIN_FACTOR: f32 = 2.0 * DELTA_TIME;
+#[derive(Debug)]
+pub struct XPBDPlugin {
+    /// Initial timestep and substep count, which can later be changed through the
+    /// `PhysicsConfig` resource
+    pub config: PhysicsConfig,
+    /// Schedule which runs `PhysicsSchedule`, advancing the simulation by one timestep each
+    /// time it runs, `FixedUpdate` by default. Pass `PhysicsSchedule` itself to only step by
+    /// hand with `PhysicsWorld::step`. `Interpolation` relies on `Time<Fixed>`, so only suits
+    /// `FixedUpdate`.
+    pub schedule: InternedScheduleLabel,
+}
 
-#[derive(Debug, Hash, PartialEq, Eq, Clone, SystemSet)]
-struct FixedUpdateSet;
+impl Default for XPBDPlugin {
+    fn default() -> Self {
+        Self::new(FixedUpdate)
+    }
+}
 
-#[derive(Debug, Default)]
-pub struct XPBDPlugin;
+impl XPBDPlugin {
+    /// Plugin stepping the simulation in `schedule`, such as `PostUpdate` or a schedule run
+    /// by hand
+    pub fn new(schedule: impl ScheduleLabel) -> Self {
+        Self {
+            config: PhysicsConfig::default(),
+            schedule: schedule.intern(),
+        }
+    }
+}
 
+/// Stages of the simulation, for ordering user systems against the physics. The first two run
+/// in `PhysicsSchedule` within `PhysicsSet`, the others run in `SubstepSchedule`, each in the
+/// order listed.
 #[derive(SystemSet, Debug, Hash, PartialEq, Eq, Clone)]
-enum Step {
+pub enum Step {
+    /// Mass properties, kinematic targets and AABBs are updated, then the broad phase finds
+    /// the collision pairs for this timestep
     CollectCollisionPairs,
+    /// Runs `SubstepSchedule` once per substep
+    Substeps,
+    /// Contacts from the previous substep are cleared and bodies move under gravity and their
+    /// velocity. Systems applying forces should run before this set.
     Integrate,
+    /// Contacts and constraints correct positions, filling the `Contacts` and
+    /// `StaticContacts` resources
     SolvePositions,
+    /// Velocities are derived from the corrected positions
     UpdateVelocities,
+    /// Restitution and other velocity level corrections for this substep's contacts
     SolveVelocities,
-    Substeps,
 }
 
+/// Schedule holding one whole timestep of the simulation, run by the schedule given to
+/// `XPBDPlugin`
 #[derive(ScheduleLabel, Debug, Hash, PartialEq, Eq, Clone)]
-struct SubstepSchedule;
+pub struct PhysicsSchedule;
+
+/// Schedule run `PhysicsConfig::substeps` times in each timestep, holding the `Integrate`,
+/// `SolvePositions`, `UpdateVelocities` and `SolveVelocities` steps. Add systems to it with
+/// `app.add_systems(SubstepSchedule, ...)`.
+#[derive(ScheduleLabel, Debug, Hash, PartialEq, Eq, Clone)]
+pub struct SubstepSchedule;
+
+/// Steps the simulation directly, for tests and netcode which drive the physics themselves
+#[derive(Debug)]
+pub struct PhysicsWorld;
+
+impl PhysicsWorld {
+    /// Runs one timestep, from updating AABBs and finding collision pairs through all the
+    /// substeps. The world needs the resources and schedules added by `XPBDPlugin`.
+    pub fn step(world: &mut World) {
+        world.run_schedule(PhysicsSchedule);
+    }
+}
+
+fn run_physics_schedule(world: &mut World) {
+    PhysicsWorld::step(world);
+}
 
 fn run_substep_schedule(world: &mut World) {
-    for _substep in 0..NUM_SUBSTEPS {
+    let substeps = world.resource::<PhysicsConfig>().substeps;
+    for _substep in 0..substeps {
         world.run_schedule(SubstepSchedule);
     }
 }
+
 impl Plugin for XPBDPlugin {
     fn build(&self, app: &mut App) {
-        let mut substep_schedule = Schedule::new(SubstepSchedule);
-        substep_schedule
-            .add_systems(integrate.in_set(Step::Integrate))
-            .add_systems(
+        app.configure_sets(
+            SubstepSchedule,
+            (
+                Step::Integrate,
+                Step::SolvePositions,
+                Step::UpdateVelocities,
+                Step::SolveVelocities,
+            )
+                .chain(),
+        )
+        .add_systems(
+            SubstepSchedule,
+            (
+                clear_contacts,
                 (
-                    solve_pos,
-                    solve_pos_box_box,
-                    solve_pos_statics,
-                    solve_pos_static_boxes,
-                    solve_pos_static_box_box,
+                    (apply_gravity_sources, apply_mutual_gravity, apply_buoyancy),
+                    integrate,
                 )
-                    .in_set(Step::SolvePositions)
-                    .after(Step::Integrate),
+                    .chain(),
             )
-            .add_systems(
-                update_vel
-                    .in_set(Step::UpdateVelocities)
-                    .after(Step::SolvePositions),
+                .in_set(Step::Integrate),
+        )
+        .add_systems(
+            SubstepSchedule,
+            (
+                solve_pos,
+                solve_pos_box_box,
+                solve_pos_statics,
+                solve_pos_static_boxes,
+                solve_pos_static_box_box,
+                solve_point_constraints,
+                solve_distance_constraints,
+                solve_shape_matching_constraints,
+                solve_area_constraints,
+                solve_fluid_density,
+                solve_granular_friction
+                    .after(solve_pos)
+                    .after(solve_pos_statics)
+                    .after(solve_pos_static_boxes),
             )
-            .add_systems(
-                (solve_vel, solve_vel_statics)
-                    .in_set(Step::SolveVelocities)
-                    .after(Step::UpdateVelocities),
-            );
-        app.init_resource::<Gravity>()
+                .in_set(Step::SolvePositions),
+        )
+        .add_systems(SubstepSchedule, update_vel.in_set(Step::UpdateVelocities))
+        .add_systems(
+            SubstepSchedule,
+            (
+                solve_vel,
+                solve_vel_statics,
+                apply_fluid_viscosity,
+                apply_rolling_resistance
+                    .after(solve_vel)
+                    .after(solve_vel_statics),
+            )
+                .in_set(Step::SolveVelocities),
+        );
+        app.add_event::<ConstraintTorn>()
+            .init_resource::<Gravity>()
+            .init_resource::<FluidConfig>()
+            .init_resource::<MutualGravitySettings>()
             .init_resource::<CollisionPairs>()
             .init_resource::<Contacts>()
             .init_resource::<StaticContacts>();
-        app.add_schedule(substep_schedule);
-        app.insert_resource(Time::<Fixed>::from_seconds(DELTA_TIME.into()))
+        let config = match self.config.validate() {
+            Ok(()) => self.config,
+            Err(config_error) => {
+                error!("Replacing invalid physics config: {config_error}");
+                self.config.sanitized()
+            }
+        };
+        app.insert_resource(config)
+            .insert_resource(Time::<Fixed>::from_seconds(config.timestep.into()))
             .add_systems(
-                FixedUpdate,
-                (update_aabb_box, update_aabb_circle).before(Step::CollectCollisionPairs),
+                PreUpdate,
+                (validate_physics_config, sync_fixed_timestep)
+                    .chain()
+                    .run_if(resource_changed::<PhysicsConfig>),
+            )
+            .init_resource::<PhysicsTime>()
+            .init_resource::<Interpolation>()
+            .configure_sets(
+                PhysicsSchedule,
+                (Step::CollectCollisionPairs, Step::Substeps)
+                    .chain()
+                    .in_set(PhysicsSet),
             )
+            .configure_sets(PhysicsSchedule, PhysicsSet.run_if(physics_time_advancing))
             .add_systems(
-                Update,
-                collect_collision_pairs
-                    .in_set(Step::CollectCollisionPairs)
-                    .before(Step::Substeps),
+                PhysicsSchedule,
+                (
+                    validate_physics_config.run_if(resource_changed::<PhysicsConfig>),
+                    advance_physics_time,
+                )
+                    .chain()
+                    .before(PhysicsSet),
             )
             .add_systems(
-                Update,
-                run_substep_schedule
-                    .in_set(Step::Substeps)
-                    .before(Step::SolveVelocities),
+                PhysicsSchedule,
+                (
+                    (init_prev_pos, record_prev_step_pos, update_mass_properties),
+                    (update_kinematic_targets, apply_external_impulses),
+                    (update_aabb_box, update_aabb_circle),
+                    collect_collision_pairs,
+                )
+                    .chain()
+                    .in_set(Step::CollectCollisionPairs),
+            )
+            .add_systems(PhysicsSchedule, run_substep_schedule.in_set(Step::Substeps))
+            .add_systems(
+                PhysicsSchedule,
+                tear_constraints.in_set(PhysicsSet).after(Step::Substeps),
             )
-            .add_systems(Update, sync_transforms.after(Step::Substeps));
+            .add_systems(
+                Update,
+                (
+                    (remove_torn_triangles, insert_prev_step_pos),
+                    (sync_transforms, sync_deformable_meshes),
+                )
+                    .chain(),
+            );
+        if self.schedule != PhysicsSchedule.intern() {
+            app.add_systems(self.schedule, run_physics_schedule);
+        }
     }
 }
 
@@ -129,36 +313,196 @@ fn collect_collision_pairs(
     }
 }
 
-//fn clear_contacts(mut contacts: ResMut<Contacts>, mut static_contacts: ResMut<StaticContacts>) {
-//    contacts.0.clear();
-//    static_contacts.0.clear();
-//}
+/// keeps inverse mass and inertia in step with `Mass`, deriving the mass from `Density` when set.
+/// Bodies with an invalid mass are given infinite mass, so that forces and contacts cannot move
+/// them, rather than keeping whatever inverse mass they had.
+#[allow(clippy::type_complexity)]
+fn update_mass_properties(
+    mut query: Query<
+        (
+            Entity,
+            &mut Mass,
+            &mut InvMass,
+            &mut Inertia,
+            Option<&Density>,
+            Option<&CircleCollider>,
+            Option<&BoxCollider>,
+        ),
+        Or<(
+            Changed<Mass>,
+            Changed<Density>,
+            Changed<CircleCollider>,
+            Changed<BoxCollider>,
+        )>,
+    >,
+) {
+    for (entity, mut mass, mut inv_mass, mut inertia, density, circle, r#box) in query.iter_mut() {
+        let kilograms = density.map_or(mass.0, |density| {
+            let area = circle
+                .map(CircleCollider::area)
+                .or_else(|| r#box.map(BoxCollider::area))
+                .unwrap_or_default();
+            density.0 * area
+        });
+        let valid_mass = match Mass::try_new(kilograms) {
+            Ok(valid_mass) => valid_mass,
+            Err(mass_error) => {
+                error!("Treating {entity:?} as infinitely heavy: {mass_error}");
+                inv_mass.0 = 0.;
+                inertia.0 = 0.;
+                continue;
+            }
+        };
+
+        inv_mass.0 = 1. / valid_mass.0;
+        inertia.0 = circle
+            .map(|circle| circle.inertia(valid_mass.0))
+            .or_else(|| r#box.map(|r#box| r#box.inertia(valid_mass.0)))
+            .unwrap_or_default();
+        mass.set_if_neq(valid_mass);
+    }
+}
+
+fn advance_physics_time(mut time: ResMut<PhysicsTime>, config: Res<PhysicsConfig>) {
+    time.advance(&config);
+}
+
+fn physics_time_advancing(time: Res<PhysicsTime>) -> bool {
+    time.delta_seconds() > 0.
+}
+
+/// puts back the defaults for any invalid values set at runtime, before they are used
+fn validate_physics_config(mut config: ResMut<PhysicsConfig>) {
+    if let Err(config_error) = config.validate() {
+        error!("Replacing invalid physics config: {config_error}");
+        *config = config.sanitized();
+    }
+}
+
+fn sync_fixed_timestep(config: Res<PhysicsConfig>, mut time: ResMut<Time<Fixed>>) {
+    time.set_timestep_seconds(config.timestep.into());
+}
+
+/// starts newly spawned bodies off one substep behind, consistent with their velocity
+fn init_prev_pos(
+    mut query: Query<(&mut PrevPos, &Pos, Option<&Vel>), Added<PrevPos>>,
+    time: Res<PhysicsTime>,
+) {
+    for (mut prev_pos, pos, vel) in query.iter_mut() {
+        let vel = vel.map_or(Vec2::ZERO, |vel| vel.0);
+        prev_pos.0 = pos.0 - vel * time.substep_delta_seconds();
+    }
+}
+
+fn apply_external_impulses(
+    mut query: Query<(&mut Vel, &mut ExternalImpulse, &InvMass, &RigidBody)>,
+) {
+    for (mut vel, mut impulse, inv_mass, rigid_body) in query.iter_mut() {
+        if impulse.0 == Vec2::ZERO {
+            continue;
+        }
+        if rigid_body.is_dynamic() {
+            vel.0 += impulse.0 * inv_mass.0;
+        }
+        impulse.0 = Vec2::ZERO;
+    }
+}
+
+/// sets kinematic body velocities so that they reach their target by the end of the step
+fn update_kinematic_targets(
+    mut query: Query<(&mut Vel, &Pos, &RigidBody, &KinematicTarget)>,
+    time: Res<PhysicsTime>,
+) {
+    for (mut vel, pos, rigid_body, target) in query.iter_mut() {
+        if rigid_body.is_kinematic() {
+            vel.0 = (target.0 - pos.0) / time.delta_seconds();
+        }
+    }
+}
+
+fn clear_contacts(mut contacts: ResMut<Contacts>, mut static_contacts: ResMut<StaticContacts>) {
+    contacts.0.clear();
+    static_contacts.0.clear();
+}
 
+#[allow(clippy::type_complexity)]
 fn integrate(
-    mut query: Query<(&mut Pos, &mut PrevPos, &mut Vel, &mut PreSolveVel, &Mass)>,
+    mut query: Query<(
+        &mut Pos,
+        &mut PrevPos,
+        &mut Vel,
+        &mut PreSolveVel,
+        &RigidBody,
+        Option<(&ExternalForce, &InvMass)>,
+        Option<&GravityScale>,
+        Option<&GravityOverride>,
+        Option<&LinearDamping>,
+        Option<&QuadraticDrag>,
+    )>,
     gravity: Res<Gravity>,
+    config: Res<PhysicsConfig>,
+    time: Res<PhysicsTime>,
 ) {
-    for (mut pos, mut prev_pos, mut vel, mut pre_sol_velocity, mass) in query.iter_mut() {
+    let sub_dt = time.substep_delta_seconds();
+    for (
+        mut pos,
+        mut prev_pos,
+        mut vel,
+        mut pre_sol_velocity,
+        rigid_body,
+        force,
+        gravity_scale,
+        gravity_override,
+        linear_damping,
+        quadratic_drag,
+    ) in query.iter_mut()
+    {
+        if rigid_body.is_static() {
+            continue;
+        }
         prev_pos.0 = pos.0;
 
-        let gravitation_force = mass.0 * gravity.0;
-        let external_forces = gravitation_force;
-        vel.0 += SUB_DT * external_forces / mass.0;
-        pos.0 += SUB_DT * vel.0;
+        if rigid_body.is_dynamic() {
+            let acceleration = gravity.for_body(gravity_override, gravity_scale);
+            // gravitational acceleration is the same for every mass, including infinite ones
+            vel.0 += sub_dt * acceleration;
+            if let Some((force, inv_mass)) = force {
+                vel.0 += sub_dt * force.0 * inv_mass.0;
+            }
+            vel.0 = damp(
+                vel.0,
+                linear_damping.map_or(config.linear_damping, |damping| damping.0),
+                quadratic_drag.map_or(config.quadratic_drag, |drag| drag.0),
+                sub_dt,
+            );
+        }
+        pos.0 += sub_dt * vel.0;
         pre_sol_velocity.0 = vel.0;
     }
 }
 
+/// velocity after `sub_dt` seconds of damping and drag, using the exact solution of
+/// dv/dt = -(linear_damping + quadratic_drag |v|) v so that the result does not depend on the
+/// number of substeps
+fn damp(vel: Vec2, linear_damping: f32, quadratic_drag: f32, sub_dt: f32) -> Vec2 {
+    let decay = (-linear_damping * sub_dt).exp();
+    // how far the drag term has acted, which tends to sub_dt as the damping goes to zero
+    let drag_time = if linear_damping > 0. {
+        -(-linear_damping * sub_dt).exp_m1() / linear_damping
+    } else {
+        sub_dt
+    };
+    vel * decay / (1. + quadratic_drag * vel.length() * drag_time)
+}
+
 fn constrain_body_positions(
     pos_a: &mut Pos,
     pos_b: &mut Pos,
-    mass_a: &Mass,
-    mass_b: &Mass,
+    w_a: f32,
+    w_b: f32,
     normal: Vec2,
     penetration_depth: f32,
 ) {
-    let w_a = 1.0 / mass_a.0;
-    let w_b = 1.0 / mass_b.0;
     let w_sum = w_a + w_b;
     let pos_impulse = normal * (-penetration_depth / w_sum);
     pos_a.0 += pos_impulse * w_a;
@@ -169,151 +513,212 @@ fn constrain_body_position(pos: &mut Pos, normal: Vec2, penetration_depth: f32)
     pos.0 -= normal * penetration_depth;
 }
 
+/// Inverse masses for a colliding pair. Pairs involving a static body are left to the static
+/// solvers and pairs where neither body can move are skipped.
+fn pair_inverse_masses(
+    (rigid_body_a, inv_mass_a): (&RigidBody, &InvMass),
+    (rigid_body_b, inv_mass_b): (&RigidBody, &InvMass),
+) -> Option<(f32, f32)> {
+    if rigid_body_a.is_static() || rigid_body_b.is_static() {
+        return None;
+    }
+    let w_a = rigid_body_a.inverse_mass(inv_mass_a);
+    let w_b = rigid_body_b.inverse_mass(inv_mass_b);
+    (w_a + w_b > 0.).then_some((w_a, w_b))
+}
+
 fn solve_pos(
-    query: Query<(&mut Pos, &CircleCollider, &Mass)>,
-    //mut contacts: ResMut<Contacts>,
+    query: Query<(&mut Pos, &CircleCollider, &InvMass, &RigidBody)>,
+    mut contacts: ResMut<Contacts>,
     collision_pairs: Res<CollisionPairs>,
 ) {
     debug!("  solve_pos");
     for (entity_a, entity_b) in collision_pairs.0.iter().cloned() {
-        if let (Ok((mut pos_a, circle_a, mass_a)), Ok((mut pos_b, circle_b, mass_b))) = unsafe {
+        if let (
+            Ok((mut pos_a, circle_a, inv_mass_a, rigid_body_a)),
+            Ok((mut pos_b, circle_b, inv_mass_b, rigid_body_b)),
+        ) = unsafe {
             assert!(entity_a != entity_b); // Ensure we don't violate memory constraints
             (query.get_unchecked(entity_a), query.get_unchecked(entity_b))
         } {
+            let Some((w_a, w_b)) =
+                pair_inverse_masses((rigid_body_a, inv_mass_a), (rigid_body_b, inv_mass_b))
+            else {
+                continue;
+            };
             if let Some(Contact {
                 normal,
                 penetration,
             }) = contact::ball_ball(pos_a.0, circle_a.radius, pos_b.0, circle_b.radius)
             {
-                constrain_body_positions(
-                    &mut pos_a,
-                    &mut pos_b,
-                    mass_a,
-                    mass_b,
-                    normal,
-                    penetration,
-                );
-                // contacts.0.push((entity_a, entity_b, normal));
+                constrain_body_positions(&mut pos_a, &mut pos_b, w_a, w_b, normal, penetration);
+                contacts.0.push((entity_a, entity_b, normal, penetration));
             }
         }
     }
 }
 
 fn solve_pos_statics(
-    mut dynamics: Query<(Entity, &mut Pos, &CircleCollider), With<Mass>>,
-    statics: Query<(Entity, &Pos, &CircleCollider), Without<Mass>>,
+    mut query: Query<(Entity, &mut Pos, &CircleCollider, &RigidBody)>,
     mut contacts: ResMut<StaticContacts>,
 ) {
-    for (entity_a, mut pos_a, circle_a) in dynamics.iter_mut() {
-        for (entity_b, pos_b, circle_b) in statics.iter() {
+    let statics: Vec<(Entity, Vec2, f32)> = query
+        .iter()
+        .filter(|(_, _, _, rigid_body)| rigid_body.is_static())
+        .map(|(entity, pos, circle, _)| (entity, pos.0, circle.radius))
+        .collect();
+
+    for (entity_a, mut pos_a, circle_a, rigid_body_a) in query.iter_mut() {
+        if !rigid_body_a.is_dynamic() {
+            continue;
+        }
+        for &(entity_b, pos_b, radius_b) in &statics {
             if let Some(Contact {
                 normal,
                 penetration,
-            }) = contact::ball_ball(pos_a.0, circle_a.radius, pos_b.0, circle_b.radius)
+            }) = contact::ball_ball(pos_a.0, circle_a.radius, pos_b, radius_b)
             {
                 constrain_body_position(&mut pos_a, normal, penetration);
-                contacts.0.push((entity_a, entity_b, normal));
+                contacts.0.push((entity_a, entity_b, normal, penetration));
             }
         }
     }
 }
 
 fn solve_pos_static_boxes(
-    mut dynamics: Query<(Entity, &mut Pos, &CircleCollider), With<Mass>>,
-    statics: Query<(Entity, &Pos, &BoxCollider), Without<Mass>>,
+    mut dynamics: Query<(Entity, &mut Pos, &CircleCollider, &RigidBody), Without<BoxCollider>>,
+    statics: Query<(Entity, &Pos, &BoxCollider, &RigidBody), Without<CircleCollider>>,
     mut contacts: ResMut<StaticContacts>,
 ) {
-    for (entity_a, mut pos_a, circle_a) in dynamics.iter_mut() {
-        for (entity_b, pos_b, box_b) in statics.iter() {
+    for (entity_a, mut pos_a, circle_a, rigid_body_a) in dynamics.iter_mut() {
+        if !rigid_body_a.is_dynamic() {
+            continue;
+        }
+        for (entity_b, pos_b, box_b, rigid_body_b) in statics.iter() {
+            if !rigid_body_b.is_static() {
+                continue;
+            }
             if let Some(Contact {
                 normal,
                 penetration,
             }) = contact::ball_box(pos_a.0, circle_a.radius, pos_b.0, box_b.size)
             {
                 constrain_body_position(&mut pos_a, normal, penetration);
-                contacts.0.push((entity_a, entity_b, normal));
+                contacts.0.push((entity_a, entity_b, normal, penetration));
             }
         }
     }
 }
 
 fn solve_pos_box_box(
-    query: Query<(&mut Pos, &BoxCollider, &Mass)>,
+    query: Query<(&mut Pos, &BoxCollider, &InvMass, &RigidBody)>,
     mut contacts: ResMut<Contacts>,
     collision_pairs: Res<CollisionPairs>,
 ) {
     for (entity_a, entity_b) in collision_pairs.0.iter().cloned() {
-        if let (Ok((mut pos_a, box_a, mass_a)), Ok((mut pos_b, box_b, mass_b))) = unsafe {
+        if let (
+            Ok((mut pos_a, box_a, inv_mass_a, rigid_body_a)),
+            Ok((mut pos_b, box_b, inv_mass_b, rigid_body_b)),
+        ) = unsafe {
             assert!(entity_a != entity_b); // Ensure we don't violate memory constraints
             (query.get_unchecked(entity_a), query.get_unchecked(entity_b))
         } {
+            let Some((w_a, w_b)) =
+                pair_inverse_masses((rigid_body_a, inv_mass_a), (rigid_body_b, inv_mass_b))
+            else {
+                continue;
+            };
             if let Some(Contact {
                 normal,
                 penetration,
             }) = contact::box_box(pos_a.0, box_a.size, pos_b.0, box_b.size)
             {
-                constrain_body_positions(
-                    &mut pos_a,
-                    &mut pos_b,
-                    mass_a,
-                    mass_b,
-                    normal,
-                    penetration,
-                );
-                contacts.0.push((entity_a, entity_b, normal));
+                constrain_body_positions(&mut pos_a, &mut pos_b, w_a, w_b, normal, penetration);
+                contacts.0.push((entity_a, entity_b, normal, penetration));
             }
         }
     }
 }
 
 fn solve_pos_static_box_box(
-    mut dynamics: Query<(Entity, &mut Pos, &BoxCollider), With<Mass>>,
-    statics: Query<(Entity, &Pos, &BoxCollider), Without<Mass>>,
+    mut query: Query<(Entity, &mut Pos, &BoxCollider, &RigidBody)>,
     mut contacts: ResMut<StaticContacts>,
 ) {
-    for (entity_a, mut pos_a, box_a) in dynamics.iter_mut() {
-        for (entity_b, pos_b, box_b) in statics.iter() {
+    let statics: Vec<(Entity, Vec2, Vec2)> = query
+        .iter()
+        .filter(|(_, _, _, rigid_body)| rigid_body.is_static())
+        .map(|(entity, pos, r#box, _)| (entity, pos.0, r#box.size))
+        .collect();
+
+    for (entity_a, mut pos_a, box_a, rigid_body_a) in query.iter_mut() {
+        if !rigid_body_a.is_dynamic() {
+            continue;
+        }
+        for &(entity_b, pos_b, size_b) in &statics {
             if let Some(Contact {
                 normal,
                 penetration,
-            }) = contact::box_box(pos_a.0, box_a.size, pos_b.0, box_b.size)
+            }) = contact::box_box(pos_a.0, box_a.size, pos_b, size_b)
             {
                 constrain_body_position(&mut pos_a, normal, penetration);
-                contacts.0.push((entity_a, entity_b, normal));
+                contacts.0.push((entity_a, entity_b, normal, penetration));
             }
         }
     }
 }
 
-fn update_aabb_circle(mut query: Query<(&mut Aabb, &Pos, &Vel, &CircleCollider)>) {
+fn update_aabb_circle(
+    mut query: Query<(&mut Aabb, &Pos, &Vel, &CircleCollider)>,
+    config: Res<PhysicsConfig>,
+) {
+    let margin_factor = config.collision_pair_vel_margin_factor();
     for (mut aabb, pos, vel, circle) in query.iter_mut() {
-        let margin = COLLISION_PAIR_VEL_MARGIN_FACTOR * vel.0.length();
+        let margin = margin_factor * vel.0.length();
         let half_extents = Vec2::splat(circle.radius + margin);
         aabb.min = pos.0 - half_extents;
         aabb.max = pos.0 + half_extents;
     }
 }
 
-fn update_aabb_box(mut query: Query<(&mut Aabb, &Pos, &Vel, &BoxCollider)>) {
+fn update_aabb_box(
+    mut query: Query<(&mut Aabb, &Pos, &Vel, &BoxCollider)>,
+    config: Res<PhysicsConfig>,
+) {
+    let margin_factor = config.collision_pair_vel_margin_factor();
     for (mut aabb, pos, vel, r#box) in query.iter_mut() {
-        let margin = COLLISION_PAIR_VEL_MARGIN_FACTOR * vel.0.length();
+        let margin = margin_factor * vel.0.length();
         let half_extents = r#box.size / 2.0 + Vec2::splat(margin);
         aabb.min = pos.0 - half_extents;
         aabb.max = pos.0 + half_extents;
     }
 }
 
-fn update_vel(mut query: Query<(&mut Pos, &mut PrevPos, &mut Vel)>) {
-    for (pos, prev_pos, mut vel) in query.iter_mut() {
-        vel.0 = (pos.0 - prev_pos.0) / SUB_DT;
+/// Kinematic bodies keep the velocity they were given, so only dynamic bodies are updated
+/// Velocity from the distance moved over the substep. Only the solver's corrections are turned
+/// back into velocity, as the rounding of `pos - prev_pos` far from the origin would otherwise
+/// swamp small velocity changes such as damping.
+fn update_vel(
+    mut query: Query<(&Pos, &PrevPos, &mut Vel, &PreSolveVel, &RigidBody)>,
+    time: Res<PhysicsTime>,
+) {
+    let sub_dt = time.substep_delta_seconds();
+    for (pos, prev_pos, mut vel, pre_solve_vel, rigid_body) in query.iter_mut() {
+        if rigid_body.is_dynamic() {
+            // the same sum as in `integrate`, so exactly zero correction when nothing pushed
+            let integrated = prev_pos.0 + sub_dt * pre_solve_vel.0;
+            vel.0 = pre_solve_vel.0 + (pos.0 - integrated) / sub_dt;
+        }
     }
 }
 
-fn solve_vel(query: Query<(&mut Vel, &PreSolveVel, &Mass, &Restitution)>, contacts: Res<Contacts>) {
-    for (entity_a, entity_b, normal) in contacts.0.iter().cloned() {
+fn solve_vel(
+    query: Query<(&mut Vel, &PreSolveVel, &InvMass, &Restitution, &RigidBody)>,
+    contacts: Res<Contacts>,
+) {
+    for (entity_a, entity_b, normal, _) in contacts.0.iter().cloned() {
         let (
-            (mut vel_a, pre_solve_vel_a, mass_a, restitution_a),
-            (mut vel_b, pre_solve_vel_b, mass_b, restitution_b),
+            (mut vel_a, pre_solve_vel_a, inv_mass_a, restitution_a, rigid_body_a),
+            (mut vel_b, pre_solve_vel_b, inv_mass_b, restitution_b, rigid_body_b),
         ) = unsafe {
             // Ensure safety
             assert!(entity_a != entity_b);
@@ -329,8 +734,8 @@ fn solve_vel(query: Query<(&mut Vel, &PreSolveVel, &Mass, &Restitution)>, contac
         let normal_vel = Vec2::dot(relative_vel, normal);
         let restitution = (restitution_a.0 + restitution_b.0) / 2.;
 
-        let w_a = 1. / mass_a.0;
-        let w_b = 1. / mass_b.0;
+        let w_a = rigid_body_a.inverse_mass(inv_mass_a);
+        let w_b = rigid_body_b.inverse_mass(inv_mass_b);
         let w_sum = w_a + w_b;
 
         let restitution_velocity = (-restitution * pre_solve_normal_vel).min(0.);
@@ -342,11 +747,11 @@ fn solve_vel(query: Query<(&mut Vel, &PreSolveVel, &Mass, &Restitution)>, contac
 }
 
 fn solve_vel_statics(
-    mut dynamics: Query<(&mut Vel, &PreSolveVel, &Restitution), With<Mass>>,
-    statics: Query<&Restitution, Without<Mass>>,
+    mut dynamics: Query<(&mut Vel, &PreSolveVel, &Restitution)>,
+    statics: Query<&Restitution>,
     contacts: Res<StaticContacts>,
 ) {
-    for (entity_a, entity_b, normal) in contacts.0.iter().cloned() {
+    for (entity_a, entity_b, normal, _) in contacts.0.iter().cloned() {
         let (mut vel_a, pre_solve_vel_a, restitution_a) = dynamics.get_mut(entity_a).unwrap();
         let restitution_b = statics.get(entity_b).unwrap();
         let pre_solve_normal_vel = Vec2::dot(pre_solve_vel_a.0, normal);
@@ -357,8 +762,308 @@ fn solve_vel_statics(
 }
 
 /// copies positions from the physics world to bevy Transforms
-fn sync_transforms(mut query: Query<(&mut bevy::transform::components::Transform, &Pos)>) {
-    for (mut transform, pos) in query.iter_mut() {
-        transform.translation = pos.0.extend(0.);
+fn sync_transforms(
+    mut query: Query<(
+        &mut bevy::transform::components::Transform,
+        &Pos,
+        Option<&PrevStepPos>,
+        Option<&Vel>,
+    )>,
+    blend: RenderBlend,
+) {
+    for (mut transform, pos, prev_step_pos, vel) in query.iter_mut() {
+        transform.translation = blend.position(pos, prev_step_pos, vel).extend(0.);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::{app::App, math::Vec2, MinimalPlugins};
+
+    use super::{PhysicsSchedule, PhysicsWorld, XPBDPlugin};
+    use crate::{
+        BoxCollider, CircleCollider, Density, ExternalForce, ExternalImpulse, Gravity,
+        GravityOverride, GravityScale, InvMass, KinematicTarget, LinearDamping, Mass,
+        ParticleBundle, PhysicsConfig, PhysicsTime, Pos, QuadraticDrag, Restitution, RigidBody,
+        StaticBoxBundle, Vel,
+    };
+
+    /// Physics app to be stepped with `PhysicsWorld::step`, without global gravity
+    pub(crate) fn zero_gravity_app() -> App {
+        let mut app = App::new();
+        app.add_plugins(XPBDPlugin::new(PhysicsSchedule))
+            .insert_resource(Gravity(Vec2::ZERO));
+        app
+    }
+
+    #[test]
+    fn ball_comes_to_rest_on_floor() {
+        // arrange
+        let mut app = App::new();
+        app.add_plugins((MinimalPlugins, XPBDPlugin::new(PhysicsSchedule)));
+        app.world.spawn(StaticBoxBundle {
+            pos: Pos(Vec2::new(0., -0.5)),
+            collider: BoxCollider {
+                size: Vec2::new(10., 1.),
+            },
+            ..Default::default()
+        });
+        let ball = app
+            .world
+            .spawn(ParticleBundle {
+                collider: CircleCollider { radius: 0.5 },
+                restitution: Restitution(0.),
+                ..ParticleBundle::new_with_pos_and_vel(Vec2::new(0., 2.), Vec2::ZERO)
+            })
+            .id();
+
+        // act
+        for _ in 0..120 {
+            PhysicsWorld::step(&mut app.world);
+        }
+
+        // assert
+        let pos = app.world.get::<Pos>(ball).unwrap().0;
+        let vel = app.world.get::<Vel>(ball).unwrap().0;
+        assert!((pos.y - 0.5).abs() < 0.01, "ball at height {}", pos.y);
+        assert!(vel.length() < 0.05, "ball still moving at {vel}");
+    }
+
+    #[test]
+    fn invalid_config_is_replaced_before_use() {
+        // arrange
+        let mut app = App::new();
+        app.add_plugins((
+            MinimalPlugins,
+            XPBDPlugin {
+                config: PhysicsConfig {
+                    timestep: 0.,
+                    ..Default::default()
+                },
+                ..XPBDPlugin::new(PhysicsSchedule)
+            },
+        ));
+        let built = *app.world.resource::<PhysicsConfig>();
+
+        // act
+        app.world.resource_mut::<PhysicsConfig>().substeps = 0;
+        PhysicsWorld::step(&mut app.world);
+        app.world.resource_mut::<PhysicsConfig>().timestep = f32::NAN;
+        app.update();
+
+        // assert
+        let config = *app.world.resource::<PhysicsConfig>();
+        assert_eq!(built, PhysicsConfig::default());
+        assert_eq!(config, PhysicsConfig::default());
+        let sub_dt = app.world.resource::<PhysicsTime>().substep_delta_seconds();
+        assert_eq!(sub_dt, PhysicsConfig::default().substep_dt());
+    }
+
+    #[test]
+    fn impulse_applies_once_and_force_accelerates() {
+        // arrange
+        let mut app = zero_gravity_app();
+        let body = app
+            .world
+            .spawn((
+                ParticleBundle {
+                    mass: Mass(2.),
+                    ..Default::default()
+                },
+                ExternalImpulse(Vec2::new(4., 0.)),
+                ExternalForce(Vec2::new(0., 2.)),
+            ))
+            .id();
+
+        // act
+        for _ in 0..60 {
+            PhysicsWorld::step(&mut app.world);
+        }
+
+        // assert
+        let vel = app.world.get::<Vel>(body).unwrap().0;
+        let impulse = app.world.get::<ExternalImpulse>(body).unwrap().0;
+        assert!((vel.x - 2.).abs() < 1e-3, "impulse gave {}", vel.x);
+        assert!((vel.y - 1.).abs() < 1e-3, "force gave {}", vel.y);
+        assert_eq!(impulse, Vec2::ZERO);
+    }
+
+    #[test]
+    fn kinematic_body_reaches_target_and_pushes_without_being_pushed() {
+        // arrange
+        let mut app = zero_gravity_app();
+        let particle = |pos, vel, rigid_body| ParticleBundle {
+            collider: CircleCollider { radius: 0.5 },
+            restitution: Restitution(0.),
+            rigid_body,
+            ..ParticleBundle::new_with_pos_and_vel(pos, vel)
+        };
+        let target = Vec2::new(0.5, 0.);
+        let kinematic = app
+            .world
+            .spawn((
+                particle(Vec2::ZERO, Vec2::ZERO, RigidBody::Kinematic),
+                KinematicTarget(target),
+            ))
+            .id();
+        let pushed = app
+            .world
+            .spawn(particle(Vec2::new(1.2, 0.), Vec2::ZERO, RigidBody::Dynamic))
+            .id();
+        let fixed_pos = Vec2::new(0., 3.);
+        let fixed = app
+            .world
+            .spawn(particle(fixed_pos, Vec2::new(1., 0.), RigidBody::Static))
+            .id();
+
+        // act
+        for _ in 0..10 {
+            PhysicsWorld::step(&mut app.world);
+        }
+
+        // assert
+        let pos = |entity| app.world.get::<Pos>(entity).unwrap().0;
+        assert!(
+            pos(kinematic).distance(target) < 1e-4,
+            "kinematic body at {}",
+            pos(kinematic)
+        );
+        assert!(pos(pushed).x > 1.49, "pushed body at {}", pos(pushed));
+        assert_eq!(pos(fixed), fixed_pos);
+    }
+
+    #[test]
+    fn invalid_mass_gives_infinite_mass() {
+        // arrange
+        let mut app = zero_gravity_app();
+        let massless = app
+            .world
+            .spawn((
+                ParticleBundle {
+                    mass: Mass(0.),
+                    ..Default::default()
+                },
+                ExternalForce(Vec2::new(3., 0.)),
+            ))
+            .id();
+        let no_density = app
+            .world
+            .spawn((
+                ParticleBundle {
+                    mass: Mass(2.),
+                    ..ParticleBundle::new_with_pos_and_vel(Vec2::new(5., 0.), Vec2::ZERO)
+                },
+                Density(0.),
+            ))
+            .id();
+
+        // act
+        for _ in 0..10 {
+            PhysicsWorld::step(&mut app.world);
+        }
+
+        // assert
+        assert_eq!(app.world.get::<InvMass>(massless).unwrap().get(), 0.);
+        assert_eq!(app.world.get::<Vel>(massless).unwrap().0, Vec2::ZERO);
+        assert_eq!(app.world.get::<InvMass>(no_density).unwrap().get(), 0.);
+        assert_eq!(app.world.get::<Mass>(no_density).unwrap().0, 2.);
+    }
+
+    #[test]
+    fn gravity_scale_and_override_replace_global_gravity() {
+        // arrange
+        let mut app = App::new();
+        app.add_plugins(XPBDPlugin::new(PhysicsSchedule));
+        let floating = app
+            .world
+            .spawn((
+                ParticleBundle::new_with_pos_and_vel(Vec2::new(-2., 0.), Vec2::ZERO),
+                GravityScale(0.),
+            ))
+            .id();
+        let sideways = app
+            .world
+            .spawn((
+                // near the origin, where f32 positions keep the rebuilt velocity accurate
+                ParticleBundle::new_with_pos_and_vel(Vec2::ZERO, Vec2::ZERO),
+                GravityOverride(Vec2::new(2., 0.)),
+                GravityScale(0.5),
+            ))
+            .id();
+
+        // act
+        for _ in 0..60 {
+            PhysicsWorld::step(&mut app.world);
+        }
+
+        // assert
+        let floating_vel = app.world.get::<Vel>(floating).unwrap().0;
+        let sideways_vel = app.world.get::<Vel>(sideways).unwrap().0;
+        assert_eq!(floating_vel, Vec2::ZERO);
+        assert!(
+            (sideways_vel - Vec2::new(1., 0.)).length() < 1e-3,
+            "sideways at {sideways_vel}"
+        );
+    }
+
+    #[test]
+    fn damping_and_drag_match_exact_solutions_for_any_substep_count() {
+        for substeps in [1, 20] {
+            // arrange
+            let mut app = zero_gravity_app();
+            app.insert_resource(PhysicsConfig {
+                substeps,
+                // global drag is overridden by each body's own
+                quadratic_drag: 100.,
+                ..Default::default()
+            });
+            let damped = app
+                .world
+                .spawn((
+                    ParticleBundle::new_with_pos_and_vel(Vec2::new(-50., 0.), Vec2::new(4., 0.)),
+                    LinearDamping(0.5),
+                    QuadraticDrag(0.),
+                ))
+                .id();
+            let dragged = app
+                .world
+                .spawn((
+                    ParticleBundle::new_with_pos_and_vel(Vec2::new(50., 0.), Vec2::new(4., 0.)),
+                    QuadraticDrag(0.25),
+                ))
+                .id();
+            let both = app
+                .world
+                .spawn((
+                    ParticleBundle::new_with_pos_and_vel(Vec2::new(0., 50.), Vec2::new(4., 0.)),
+                    LinearDamping(0.5),
+                    QuadraticDrag(0.25),
+                ))
+                .id();
+
+            // act
+            for _ in 0..60 {
+                PhysicsWorld::step(&mut app.world);
+            }
+
+            // assert
+            let damped_speed = app.world.get::<Vel>(damped).unwrap().0.x;
+            let dragged_speed = app.world.get::<Vel>(dragged).unwrap().0.x;
+            let both_speed = app.world.get::<Vel>(both).unwrap().0.x;
+            let decay = (-0.5_f32).exp();
+            let both_expected = 0.5 * 4. * decay / (0.5 + 0.25 * 4. * (1. - decay));
+            assert!(
+                (damped_speed - 4. * decay).abs() < 1e-3,
+                "{substeps} substeps damped to {damped_speed}"
+            );
+            assert!(
+                (dragged_speed - 4. / (1. + 0.25 * 4.)).abs() < 1e-3,
+                "{substeps} substeps dragged to {dragged_speed}"
+            );
+            assert!(
+                (both_speed - both_expected).abs() < 1e-3,
+                "{substeps} substeps slowed to {both_speed}"
+            );
+        }
     }
 }
@@ -0,0 +1,71 @@
+use bevy::math::Vec2;
+
+/// Distance along the ray from `origin` in unit `direction` to where it enters the circle, or
+/// zero if it starts inside
+pub(crate) fn ray_circle(origin: Vec2, direction: Vec2, centre: Vec2, radius: f32) -> Option<f32> {
+    let offset = origin - centre;
+    let b = offset.dot(direction);
+    let c = offset.length_squared() - radius * radius;
+    if c <= 0. {
+        return Some(0.);
+    }
+    let discriminant = b * b - c;
+    if b > 0. || discriminant < 0. {
+        return None;
+    }
+    Some(-b - discriminant.sqrt())
+}
+
+/// Distance along the ray from `origin` in unit `direction` to where it enters the axis aligned
+/// box, or zero if it starts inside
+pub(crate) fn ray_box(origin: Vec2, direction: Vec2, min: Vec2, max: Vec2) -> Option<f32> {
+    let inverse = direction.recip();
+    let t_1 = (min - origin) * inverse;
+    let t_2 = (max - origin) * inverse;
+    // a ray parallel to a slab gives NaN here when it starts on an edge, which max and min skip
+    let t_enter = t_1.min(t_2).max_element().max(0.);
+    let t_exit = t_1.max(t_2).min_element();
+    (t_enter <= t_exit).then_some(t_enter)
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::math::Vec2;
+
+    use super::{ray_box, ray_circle};
+
+    #[test]
+    fn ray_hits_circle_in_front_only() {
+        // arrange
+        let centre = Vec2::new(5., 0.);
+
+        // act
+        let hit = ray_circle(Vec2::ZERO, Vec2::X, centre, 1.);
+        let behind = ray_circle(Vec2::ZERO, -Vec2::X, centre, 1.);
+        let miss = ray_circle(Vec2::ZERO, Vec2::Y, centre, 1.);
+
+        // assert
+        assert_eq!(hit, Some(4.));
+        assert_eq!(behind, None);
+        assert_eq!(miss, None);
+    }
+
+    #[test]
+    fn ray_hits_box_in_front_only() {
+        // arrange
+        let min = Vec2::new(2., -1.);
+        let max = Vec2::new(3., 1.);
+
+        // act
+        let hit = ray_box(Vec2::ZERO, Vec2::X, min, max);
+        let inside = ray_box(Vec2::new(2.5, 0.), Vec2::Y, min, max);
+        let behind = ray_box(Vec2::ZERO, -Vec2::X, min, max);
+        let miss = ray_box(Vec2::ZERO, Vec2::Y, min, max);
+
+        // assert
+        assert_eq!(hit, Some(2.));
+        assert_eq!(inside, Some(0.));
+        assert_eq!(behind, None);
+        assert_eq!(miss, None);
+    }
+}
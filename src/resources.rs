@@ -1,30 +1,277 @@
+use std::{
+    error::Error,
+    fmt::{self, Display},
+};
+
 use bevy::{
     ecs::{entity::Entity, system::Resource},
     math::Vec2,
 };
 
+use crate::{GravityOverride, GravityScale};
+
 #[derive(Debug, Default, Resource)]
 pub(crate) struct CollisionPairs(pub Vec<(Entity, Entity)>);
 
+/// Contacts found this substep, as entity pairs with the contact normal and penetration depth
 #[derive(Default, Debug, Resource)]
-pub struct Contacts(pub Vec<(Entity, Entity, Vec2)>);
+pub struct Contacts(pub Vec<(Entity, Entity, Vec2, f32)>);
 
+/// Contacts between dynamic and static bodies found this substep, with the dynamic body first
 #[derive(Default, Debug, Resource)]
-pub struct StaticContacts(pub Vec<(Entity, Entity, Vec2)>);
+pub struct StaticContacts(pub Vec<(Entity, Entity, Vec2, f32)>);
 
 #[derive(Debug, Resource)]
 pub struct Gravity(pub Vec2);
 
+impl Gravity {
+    /// Gravitational acceleration felt by a body, after its override and scale
+    pub(crate) fn for_body(
+        &self,
+        gravity_override: Option<&GravityOverride>,
+        gravity_scale: Option<&GravityScale>,
+    ) -> Vec2 {
+        gravity_override.map_or(self.0, |gravity| gravity.0)
+            * gravity_scale.map_or(1., |scale| scale.0)
+    }
+}
+
+/// Timestep and substep count of the simulation, which can be changed at runtime to trade
+/// accuracy for speed, along with the default air resistance. `Time<Fixed>` is kept in step
+/// with `timestep`.
+#[derive(Clone, Copy, Debug, PartialEq, Resource)]
+pub struct PhysicsConfig {
+    /// Seconds simulated by each fixed update
+    pub timestep: f32,
+    /// Number of substeps each timestep is divided into
+    pub substeps: u32,
+    /// Default `LinearDamping` for dynamic bodies without their own
+    pub linear_damping: f32,
+    /// Default `QuadraticDrag`, or air resistance, for dynamic bodies without their own
+    pub quadratic_drag: f32,
+}
+
+impl Default for PhysicsConfig {
+    fn default() -> Self {
+        Self {
+            timestep: 1. / 60.,
+            substeps: 10,
+            linear_damping: 0.,
+            quadratic_drag: 0.,
+        }
+    }
+}
+
+impl PhysicsConfig {
+    pub fn validate(&self) -> Result<(), PhysicsConfigError> {
+        if !(self.timestep > 0. && self.timestep.is_finite()) {
+            Err(PhysicsConfigError::Timestep(self.timestep))
+        } else if self.substeps == 0 {
+            Err(PhysicsConfigError::NoSubsteps)
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Copy with any invalid values replaced by the defaults
+    pub(crate) fn sanitized(self) -> Self {
+        let default = Self::default();
+        Self {
+            timestep: if self.timestep > 0. && self.timestep.is_finite() {
+                self.timestep
+            } else {
+                default.timestep
+            },
+            substeps: if self.substeps > 0 {
+                self.substeps
+            } else {
+                default.substeps
+            },
+            ..self
+        }
+    }
+
+    /// Substep length before `PhysicsTime::time_scale` is applied, see
+    /// `PhysicsTime::substep_delta_seconds` for the one being simulated
+    pub fn substep_dt(&self) -> f32 {
+        self.timestep / self.substeps as f32
+    }
+
+    /// Safety margin bigger than the timestep added to AABBs, per unit of speed, to account for
+    /// sudden accelerations
+    pub(crate) fn collision_pair_vel_margin_factor(&self) -> f32 {
+        2. * self.timestep
+    }
+}
+
+/// Returned for a `PhysicsConfig` the simulation cannot be stepped with
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum PhysicsConfigError {
+    /// The timestep must be finite and greater than zero
+    Timestep(f32),
+    NoSubsteps,
+}
+
+impl Display for PhysicsConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Timestep(timestep) => {
+                write!(f, "timestep must be greater than zero, got {timestep}")
+            }
+            Self::NoSubsteps => write!(f, "there must be at least one substep"),
+        }
+    }
+}
+
+impl Error for PhysicsConfigError {}
+
+/// Pause, single step and slow motion controls for the simulation. While paused, each call to
+/// `step_once` lets one more fixed timestep through.
+#[derive(Debug, Resource)]
+pub struct PhysicsTime {
+    /// Multiplier on the time simulated each timestep, below 1 for slow motion
+    pub time_scale: f32,
+    paused: bool,
+    queued_steps: u32,
+    delta_seconds: f32,
+    substep_delta_seconds: f32,
+}
+
+impl Default for PhysicsTime {
+    fn default() -> Self {
+        Self {
+            time_scale: 1.,
+            paused: false,
+            queued_steps: 0,
+            delta_seconds: 0.,
+            substep_delta_seconds: 0.,
+        }
+    }
+}
+
+impl PhysicsTime {
+    pub fn pause(&mut self) {
+        self.paused = true;
+    }
+
+    pub fn resume(&mut self) {
+        self.paused = false;
+        self.queued_steps = 0;
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.paused
+    }
+
+    /// Advance a paused simulation by one timestep, does nothing while running
+    pub fn step_once(&mut self) {
+        if self.paused {
+            self.queued_steps += 1;
+        }
+    }
+
+    /// Scaled seconds simulated by the current timestep, zero when the simulation is held
+    pub fn delta_seconds(&self) -> f32 {
+        self.delta_seconds
+    }
+
+    /// Scaled seconds simulated by each substep of the current timestep
+    pub fn substep_delta_seconds(&self) -> f32 {
+        self.substep_delta_seconds
+    }
+
+    pub(crate) fn advance(&mut self, config: &PhysicsConfig) {
+        let stepping = if !self.paused {
+            true
+        } else if self.queued_steps > 0 {
+            self.queued_steps -= 1;
+            true
+        } else {
+            false
+        };
+        self.delta_seconds = if stepping {
+            config.timestep * self.time_scale.max(0.)
+        } else {
+            0.
+        };
+        self.substep_delta_seconds = self.delta_seconds / config.substeps as f32;
+    }
+}
+
 impl Default for Gravity {
     fn default() -> Self {
         Self(Vec2::new(0., -9.81))
     }
 }
 
-#[derive(Debug, Default, Resource)]
-pub struct LoopState {
-    pub has_added_time: bool,
-    pub accumulator: f32,
-    pub current_substep: u32,
-    pub substepping: bool,
+#[cfg(test)]
+mod tests {
+    use super::{PhysicsConfig, PhysicsConfigError, PhysicsTime};
+
+    #[test]
+    fn invalid_config_is_rejected_and_sanitized() {
+        // arrange
+        let config = PhysicsConfig {
+            timestep: 0.,
+            substeps: 0,
+            linear_damping: 0.5,
+            ..Default::default()
+        };
+
+        // act
+        let result = config.validate();
+        let sanitized = config.sanitized();
+
+        // assert
+        assert_eq!(result, Err(PhysicsConfigError::Timestep(0.)));
+        assert_eq!(
+            PhysicsConfig {
+                timestep: 1. / 60.,
+                ..config
+            }
+            .validate(),
+            Err(PhysicsConfigError::NoSubsteps)
+        );
+        assert_eq!(sanitized.validate(), Ok(()));
+        assert_eq!(
+            sanitized.substep_dt(),
+            PhysicsConfig::default().substep_dt()
+        );
+        assert_eq!(sanitized.linear_damping, 0.5);
+    }
+
+    #[test]
+    fn paused_time_only_advances_for_queued_steps() {
+        // arrange
+        let config = PhysicsConfig::default();
+        let mut time = PhysicsTime::default();
+        time.pause();
+        time.step_once();
+
+        // act
+        time.advance(&config);
+        let stepped = time.delta_seconds();
+        time.advance(&config);
+        let held = time.delta_seconds();
+
+        // assert
+        assert_eq!(stepped, config.timestep);
+        assert_eq!(held, 0.);
+    }
+
+    #[test]
+    fn time_scale_shortens_substeps() {
+        // arrange
+        let config = PhysicsConfig::default();
+        let mut time = PhysicsTime {
+            time_scale: 0.5,
+            ..Default::default()
+        };
+
+        // act
+        time.advance(&config);
+
+        // assert
+        assert_eq!(time.substep_delta_seconds(), config.substep_dt() * 0.5);
+    }
 }
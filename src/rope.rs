@@ -0,0 +1,130 @@
+use bevy::{
+    ecs::{entity::Entity, system::Commands},
+    math::Vec2,
+};
+
+use crate::{CircleCollider, DistanceConstraint, Mass, ParticleBundle};
+
+/// Entities making up a rope spawned by [`RopeBuilder`]
+#[derive(Debug, Default)]
+pub struct Rope {
+    /// Rope particles, ordered from the start of the rope to the end
+    pub particles: Vec<Entity>,
+    /// Distance constraint entities, one per segment
+    pub constraints: Vec<Entity>,
+}
+
+/// Builder for a chain of particles connected by distance constraints. Either end can be left
+/// free or attached to an existing body, for example a `StaticCircleBundle` to hang the rope
+/// from or a heavier `ParticleBundle` to swing on the end. Rope particles collide with
+/// everything else, so keep the particle radius below half of the segment length.
+#[derive(Debug)]
+pub struct RopeBuilder {
+    start: Vec2,
+    end: Vec2,
+    start_anchor: Option<Entity>,
+    end_anchor: Option<Entity>,
+    segments: u32,
+    compliance: f32,
+    particle_radius: f32,
+    particle_mass: f32,
+}
+
+impl RopeBuilder {
+    /// Rope running in a straight line from `start` to `end`. Its length is the distance
+    /// between the two.
+    pub fn new(start: Vec2, end: Vec2) -> Self {
+        Self {
+            start,
+            end,
+            start_anchor: None,
+            end_anchor: None,
+            segments: 10,
+            compliance: 0.,
+            particle_radius: 0.05,
+            particle_mass: 0.1,
+        }
+    }
+
+    /// Attach the start of the rope to an existing body positioned at `start`
+    pub fn with_start_anchor(mut self, anchor: Entity) -> Self {
+        self.start_anchor = Some(anchor);
+        self
+    }
+
+    /// Attach the end of the rope to an existing body positioned at `end`
+    pub fn with_end_anchor(mut self, anchor: Entity) -> Self {
+        self.end_anchor = Some(anchor);
+        self
+    }
+
+    pub fn with_segments(mut self, segments: u32) -> Self {
+        self.segments = segments.max(1);
+        self
+    }
+
+    /// Compliance of each segment, zero for an inextensible chain
+    pub fn with_compliance(mut self, compliance: f32) -> Self {
+        self.compliance = compliance;
+        self
+    }
+
+    pub fn with_particle_radius(mut self, radius: f32) -> Self {
+        self.particle_radius = radius;
+        self
+    }
+
+    pub fn with_particle_mass(mut self, mass: f32) -> Self {
+        self.particle_mass = mass;
+        self
+    }
+
+    pub fn spawn(self, commands: &mut Commands) -> Rope {
+        let segment_length = self.start.distance(self.end) / self.segments as f32;
+        let mut rope = Rope::default();
+
+        let nodes: Vec<Entity> = (0..=self.segments)
+            .map(|index| {
+                let anchor = if index == 0 {
+                    self.start_anchor
+                } else if index == self.segments {
+                    self.end_anchor
+                } else {
+                    None
+                };
+                anchor.unwrap_or_else(|| {
+                    let pos = self
+                        .start
+                        .lerp(self.end, index as f32 / self.segments as f32);
+                    let particle = commands
+                        .spawn(ParticleBundle {
+                            mass: Mass(self.particle_mass),
+                            collider: CircleCollider {
+                                radius: self.particle_radius,
+                            },
+                            ..ParticleBundle::new_with_pos_and_vel(pos, Vec2::ZERO)
+                        })
+                        .id();
+                    rope.particles.push(particle);
+                    particle
+                })
+            })
+            .collect();
+
+        rope.constraints = nodes
+            .windows(2)
+            .map(|pair| {
+                commands
+                    .spawn(DistanceConstraint::new(
+                        pair[0],
+                        pair[1],
+                        segment_length,
+                        self.compliance,
+                    ))
+                    .id()
+            })
+            .collect();
+
+        rope
+    }
+}
@@ -0,0 +1,430 @@
+use std::{collections::HashSet, f32::consts::TAU};
+
+use bevy::{
+    asset::{Assets, Handle},
+    ecs::{
+        component::Component,
+        entity::Entity,
+        event::EventReader,
+        system::{Commands, Query, ResMut},
+    },
+    math::Vec2,
+    render::{
+        mesh::{Indices, Mesh, VertexAttributeValues},
+        render_asset::RenderAssetUsages,
+        render_resource::PrimitiveTopology,
+    },
+};
+
+use crate::{
+    constraints::signed_area, interpolation::RenderBlend, AreaConstraint, CircleCollider,
+    ConstraintTorn, DistanceConstraint, Mass, ParticleBundle, Pos, PrevStepPos,
+    ShapeMatchingConstraint, Vel,
+};
+
+/// Entities making up a soft body spawned by [`SoftBodyBuilder`]
+#[derive(Debug, Default)]
+pub struct SoftBody {
+    /// One particle per lattice point, in the same order as the vertices of
+    /// [`SoftBodyBuilder::mesh`]
+    pub particles: Vec<Entity>,
+    /// Distance constraint entities for every structural, shear and bending link, or the single
+    /// shape matching constraint entity
+    pub constraints: Vec<Entity>,
+    /// Area constraint entity for bodies built with [`SoftBodyBuilder::with_pressure`]. Change
+    /// its `target_area` to inflate or deflate the body.
+    pub pressure: Option<Entity>,
+}
+
+/// Builder for jelly-like bodies made of particles linked by compliant distance constraints.
+/// Structural links join neighbouring particles, shear links brace them diagonally and bending
+/// links skip a particle to resist folding.
+#[derive(Debug)]
+pub struct SoftBodyBuilder {
+    positions: Vec<Vec2>,
+    /// Boundary particle indices, running counter-clockwise
+    outline: Vec<usize>,
+    structural: Vec<(usize, usize)>,
+    shear: Vec<(usize, usize)>,
+    bending: Vec<(usize, usize)>,
+    triangles: Vec<[u32; 3]>,
+    structural_compliance: f32,
+    shear_compliance: f32,
+    bending_compliance: f32,
+    shape_matching_compliance: Option<f32>,
+    pressure_compliance: Option<f32>,
+    particle_radius: Option<f32>,
+    particle_mass: f32,
+}
+
+impl SoftBodyBuilder {
+    fn new(positions: Vec<Vec2>, outline: Vec<usize>, triangles: Vec<[u32; 3]>) -> Self {
+        Self {
+            positions,
+            outline,
+            structural: Vec::new(),
+            shear: Vec::new(),
+            bending: Vec::new(),
+            triangles,
+            structural_compliance: 0.,
+            shear_compliance: 0.0001,
+            bending_compliance: 0.001,
+            shape_matching_compliance: None,
+            pressure_compliance: None,
+            particle_radius: None,
+            particle_mass: 0.1,
+        }
+    }
+
+    /// Rectangular lattice with its bottom left particle at `origin`
+    pub fn grid(origin: Vec2, columns: u32, rows: u32, spacing: f32) -> Self {
+        let columns = columns.max(2) as usize;
+        let rows = rows.max(2) as usize;
+        let index = |column: usize, row: usize| row * columns + column;
+
+        let positions = (0..rows)
+            .flat_map(|row| {
+                (0..columns)
+                    .map(move |column| origin + Vec2::new(column as f32, row as f32) * spacing)
+            })
+            .collect();
+        let mut triangles = Vec::new();
+        for row in 0..rows - 1 {
+            for column in 0..columns - 1 {
+                let [a, b, c, d] = [
+                    index(column, row),
+                    index(column + 1, row),
+                    index(column + 1, row + 1),
+                    index(column, row + 1),
+                ]
+                .map(|vertex| vertex as u32);
+                triangles.push([a, b, c]);
+                triangles.push([a, c, d]);
+            }
+        }
+
+        let outline = (0..columns)
+            .map(|column| index(column, 0))
+            .chain((1..rows).map(|row| index(columns - 1, row)))
+            .chain((0..columns - 1).rev().map(|column| index(column, rows - 1)))
+            .chain((1..rows - 1).rev().map(|row| index(0, row)))
+            .collect();
+
+        let mut builder = Self::new(positions, outline, triangles);
+        for row in 0..rows {
+            for column in 0..columns {
+                let i = index(column, row);
+                if column + 1 < columns {
+                    builder.structural.push((i, index(column + 1, row)));
+                }
+                if row + 1 < rows {
+                    builder.structural.push((i, index(column, row + 1)));
+                }
+                if column + 1 < columns && row + 1 < rows {
+                    builder.shear.push((i, index(column + 1, row + 1)));
+                    builder
+                        .shear
+                        .push((index(column + 1, row), index(column, row + 1)));
+                }
+                if column + 2 < columns {
+                    builder.bending.push((i, index(column + 2, row)));
+                }
+                if row + 2 < rows {
+                    builder.bending.push((i, index(column, row + 2)));
+                }
+            }
+        }
+        builder
+    }
+
+    /// Ring of particles, one per outline point. Shear links cross the body to the opposite
+    /// side of the ring. The outline should be convex for [`Self::mesh`] to fill it correctly.
+    pub fn polygon(outline: &[Vec2]) -> Self {
+        let count = outline.len();
+        assert!(count >= 3, "soft body outline needs at least three points");
+
+        let counter_clockwise = signed_area(outline) >= 0.;
+        let triangles = (1..count as u32 - 1)
+            .map(|i| {
+                if counter_clockwise {
+                    [0, i, i + 1]
+                } else {
+                    [0, i + 1, i]
+                }
+            })
+            .collect();
+
+        let ring = if counter_clockwise {
+            (0..count).collect()
+        } else {
+            (0..count).rev().collect()
+        };
+
+        let mut builder = Self::new(outline.to_vec(), ring, triangles);
+        let mut seen = HashSet::new();
+        let mut link = |links: &mut Vec<(usize, usize)>, a: usize, b: usize| {
+            let key = (a.min(b), a.max(b));
+            if a != b && seen.insert(key) {
+                links.push(key);
+            }
+        };
+        for i in 0..count {
+            link(&mut builder.structural, i, (i + 1) % count);
+        }
+        for i in 0..count {
+            link(&mut builder.shear, i, (i + count / 2) % count);
+        }
+        for i in 0..count {
+            link(&mut builder.bending, i, (i + 2) % count);
+        }
+        builder
+    }
+
+    /// Closed ring of particles approximating a circle, held inflated by a pressure constraint
+    /// rather than shear and bending links
+    pub fn balloon(centre: Vec2, radius: f32, segments: u32) -> Self {
+        let segments = segments.max(3);
+        let outline: Vec<Vec2> = (0..segments)
+            .map(|i| centre + Vec2::from_angle(i as f32 / segments as f32 * TAU) * radius)
+            .collect();
+        let mut builder = Self::polygon(&outline).with_pressure(0.);
+        builder.shear.clear();
+        builder.bending.clear();
+        builder
+    }
+
+    pub fn with_structural_compliance(mut self, compliance: f32) -> Self {
+        self.structural_compliance = compliance;
+        self
+    }
+
+    pub fn with_shear_compliance(mut self, compliance: f32) -> Self {
+        self.shear_compliance = compliance;
+        self
+    }
+
+    pub fn with_bending_compliance(mut self, compliance: f32) -> Self {
+        self.bending_compliance = compliance;
+        self
+    }
+
+    /// Hold the particles together with a single [`ShapeMatchingConstraint`] instead of the
+    /// lattice links
+    pub fn with_shape_matching(mut self, compliance: f32) -> Self {
+        self.shape_matching_compliance = Some(compliance);
+        self
+    }
+
+    /// Add an [`AreaConstraint`] keeping the area inside the outline at its starting value
+    pub fn with_pressure(mut self, compliance: f32) -> Self {
+        self.pressure_compliance = Some(compliance);
+        self
+    }
+
+    /// Particle collider radius, defaults to just under half the shortest structural link so
+    /// that neighbouring particles do not collide at rest
+    pub fn with_particle_radius(mut self, radius: f32) -> Self {
+        self.particle_radius = Some(radius);
+        self
+    }
+
+    pub fn with_particle_mass(mut self, mass: f32) -> Self {
+        self.particle_mass = mass;
+        self
+    }
+
+    /// Triangle mesh with one vertex per particle, for use with [`DeformableMesh`]
+    pub fn mesh(&self) -> Mesh {
+        let min = self
+            .positions
+            .iter()
+            .copied()
+            .reduce(Vec2::min)
+            .unwrap_or_default();
+        let max = self
+            .positions
+            .iter()
+            .copied()
+            .reduce(Vec2::max)
+            .unwrap_or_default();
+        let extent = (max - min).max(Vec2::splat(f32::EPSILON));
+
+        let positions: Vec<[f32; 3]> = self
+            .positions
+            .iter()
+            .map(|position| position.extend(0.).to_array())
+            .collect();
+        let normals = vec![[0_f32, 0., 1.]; positions.len()];
+        let uvs: Vec<[f32; 2]> = self
+            .positions
+            .iter()
+            .map(|position| {
+                let uv = (*position - min) / extent;
+                [uv.x, 1. - uv.y]
+            })
+            .collect();
+        let indices = self.triangles.iter().flatten().copied().collect();
+
+        Mesh::new(
+            PrimitiveTopology::TriangleList,
+            RenderAssetUsages::default(),
+        )
+        .with_inserted_attribute(Mesh::ATTRIBUTE_POSITION, positions)
+        .with_inserted_attribute(Mesh::ATTRIBUTE_NORMAL, normals)
+        .with_inserted_attribute(Mesh::ATTRIBUTE_UV_0, uvs)
+        .with_inserted_indices(Indices::U32(indices))
+    }
+
+    pub fn spawn(self, commands: &mut Commands) -> SoftBody {
+        let particle_radius = self.particle_radius.unwrap_or_else(|| {
+            0.45 * self
+                .structural
+                .iter()
+                .map(|&(a, b)| self.positions[a].distance(self.positions[b]))
+                .fold(f32::INFINITY, f32::min)
+        });
+
+        let particles: Vec<Entity> = self
+            .positions
+            .iter()
+            .map(|&pos| {
+                commands
+                    .spawn(ParticleBundle {
+                        mass: Mass(self.particle_mass),
+                        collider: CircleCollider {
+                            radius: particle_radius,
+                        },
+                        ..ParticleBundle::new_with_pos_and_vel(pos, Vec2::ZERO)
+                    })
+                    .id()
+            })
+            .collect();
+
+        let pressure = self.pressure_compliance.map(|compliance| {
+            let outline: Vec<Vec2> = self.outline.iter().map(|&i| self.positions[i]).collect();
+            commands
+                .spawn(AreaConstraint::new(
+                    self.outline.iter().map(|&i| particles[i]).collect(),
+                    signed_area(&outline),
+                    compliance,
+                ))
+                .id()
+        });
+
+        if let Some(compliance) = self.shape_matching_compliance {
+            let constraint = commands
+                .spawn(ShapeMatchingConstraint::new(
+                    particles.clone(),
+                    self.positions,
+                    compliance,
+                ))
+                .id();
+            return SoftBody {
+                particles,
+                constraints: vec![constraint],
+                pressure,
+            };
+        }
+
+        let constraints = [
+            (&self.structural, self.structural_compliance),
+            (&self.shear, self.shear_compliance),
+            (&self.bending, self.bending_compliance),
+        ]
+        .into_iter()
+        .flat_map(|(links, compliance)| links.iter().map(move |&link| (link, compliance)))
+        .map(|((a, b), compliance)| {
+            let rest_length = self.positions[a].distance(self.positions[b]);
+            commands
+                .spawn(DistanceConstraint::new(
+                    particles[a],
+                    particles[b],
+                    rest_length,
+                    compliance,
+                ))
+                .id()
+        })
+        .collect();
+
+        SoftBody {
+            particles,
+            constraints,
+            pressure,
+        }
+    }
+}
+
+/// Rewrites the vertex positions of the entity's mesh from particle positions, with vertex `i`
+/// following `particles[i]`. Triangles spanning a torn constraint are removed from the mesh.
+/// Leave the entity's `Transform` at identity and do not give it a `Pos`. Adding
+/// `NoFrustumCulling` stops the mesh vanishing once it moves away from where it started.
+#[derive(Component, Debug)]
+pub struct DeformableMesh {
+    pub particles: Vec<Entity>,
+}
+
+/// copies particle positions into the vertices of deformable meshes
+pub(crate) fn sync_deformable_meshes(
+    meshes: Option<ResMut<Assets<Mesh>>>,
+    deformables: Query<(&Handle<Mesh>, &DeformableMesh)>,
+    particles: Query<(&Pos, Option<&PrevStepPos>, Option<&Vel>)>,
+    blend: RenderBlend,
+) {
+    let Some(mut meshes) = meshes else {
+        return;
+    };
+    for (handle, deformable) in deformables.iter() {
+        let Some(mesh) = meshes.get_mut(handle) else {
+            continue;
+        };
+        let Some(VertexAttributeValues::Float32x3(positions)) =
+            mesh.attribute_mut(Mesh::ATTRIBUTE_POSITION)
+        else {
+            continue;
+        };
+        for (position, particle) in positions.iter_mut().zip(&deformable.particles) {
+            if let Ok((pos, prev_step_pos, vel)) = particles.get(*particle) {
+                *position = blend
+                    .position(pos, prev_step_pos, vel)
+                    .extend(0.)
+                    .to_array();
+            }
+        }
+    }
+}
+
+/// drops mesh triangles with an edge along a constraint which has just torn
+pub(crate) fn remove_torn_triangles(
+    mut torn: EventReader<ConstraintTorn>,
+    meshes: Option<ResMut<Assets<Mesh>>>,
+    deformables: Query<(&Handle<Mesh>, &DeformableMesh)>,
+) {
+    let Some(mut meshes) = meshes else {
+        torn.clear();
+        return;
+    };
+    for event in torn.read() {
+        for (handle, deformable) in deformables.iter() {
+            let vertex = |entity: Entity| {
+                deformable
+                    .particles
+                    .iter()
+                    .position(|&particle| particle == entity)
+                    .map(|index| index as u32)
+            };
+            let (Some(a), Some(b)) = (vertex(event.entity_a), vertex(event.entity_b)) else {
+                continue;
+            };
+            let Some(Indices::U32(indices)) = meshes.get_mut(handle).and_then(Mesh::indices_mut)
+            else {
+                continue;
+            };
+            *indices = indices
+                .chunks_exact(3)
+                .filter(|triangle| !(triangle.contains(&a) && triangle.contains(&b)))
+                .flatten()
+                .copied()
+                .collect();
+        }
+    }
+}